@@ -0,0 +1,154 @@
+//! Exports a whole planet as a PNG overview map, without starting the game.
+//!
+//! Usage:
+//! `toppa_map <output.png> savegame <name> [options]` reads `./savegames/<name>` without changing it,
+//! `toppa_map <output.png> seed <planet_dim> <chunk_dim> <seed> [options]` generates a new planet,
+//! dimensions are given as `<rows>x<columns>`.
+//!
+//! Options:
+//! `--scale <n>` draws every tile as n×n pixels, `--grid` draws the chunk borders,
+//! `--palette <path>` uses another palette than `Prefabs/map_palette.ron`,
+//! `--tables <dir>` uses other world generation tables than `Prefabs/world_gen`.
+use std::{env, path::Path, process};
+
+extern crate pretty_env_logger;
+
+extern crate toppa_drill_lib;
+use toppa_drill_lib::{
+    generation::WorldGenTables, load_ron, parse_dim, parse_seed, MapPalette, OverviewMap,
+    SavegamePaths, DEFAULT_TABLES_DIR,
+};
+
+const USAGE: &str = "Usage: toppa_map <output.png> savegame <name> [options]\n\
+                     \x20      toppa_map <output.png> seed <planet_dim> <chunk_dim> <seed> [options]\n\
+                     Dimensions are given as <rows>x<columns>.\n\
+                     Options: --scale <n>, --grid, --palette <path>, --tables <dir>";
+
+/// Where the tiles of the map come from.
+enum Source {
+    Savegame(String,),
+    Seed {
+        planet_dim: (u64, u64,),
+        chunk_dim:  (u64, u64,),
+        seed:       u64,
+    },
+}
+
+/// The parsed command line arguments.
+struct Args {
+    output:       String,
+    source:       Source,
+    scale:        u32,
+    grid_lines:   bool,
+    palette_path: String,
+    tables_dir:   String,
+}
+
+fn main() {
+    if env::var("RUST_LOG",).is_err() {
+        env::set_var("RUST_LOG", "info",);
+    }
+    pretty_env_logger::init();
+
+    let args = match parse_args(env::args().skip(1,).collect(),) {
+        Ok(args,) => args,
+        Err(e,) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1,);
+        },
+    };
+
+    if let Err(e,) = export(&args,) {
+        eprintln!("{}", e);
+        process::exit(1,);
+    }
+}
+
+fn export(args: &Args,) -> Result<(), String,> {
+    let palette: MapPalette = load_ron(Path::new(&args.palette_path,),)
+        .map_err(|e| format!("Could not load the palette {:?}, {}.", args.palette_path, e),)?;
+    let tables = WorldGenTables::load_dir(Path::new(&args.tables_dir,),);
+
+    let map = match args.source {
+        Source::Savegame(ref name,) => {
+            // Only read, so the savegame can be inspected while it is being played.
+            let paths = SavegamePaths::existing("./", name,)
+                .ok_or_else(|| format!("Savegame {:?} does not exist.", name),)?;
+            OverviewMap::from_savegame(&paths, &tables,)
+        },
+        Source::Seed {
+            planet_dim,
+            chunk_dim,
+            seed,
+        } => OverviewMap::from_seed(seed, planet_dim, chunk_dim, &tables,),
+    }
+    .map_err(|_| "Could not collect the planet's tiles.".to_string(),)?;
+
+    map.write_png(
+        Path::new(&args.output,),
+        &palette,
+        args.scale,
+        args.grid_lines,
+    )
+    .map_err(|_| format!("Could not write {:?}.", args.output),)?;
+
+    println!(
+        "Exported {}x{} tiles to {:?}.",
+        map.tile_count.0, map.tile_count.1, args.output
+    );
+    Ok((),)
+}
+
+fn parse_args(args: Vec<String,>,) -> Result<Args, String,> {
+    let mut positional = Vec::new();
+    let mut scale = 1;
+    let mut grid_lines = false;
+    let mut palette_path = "./Prefabs/map_palette.ron".to_string();
+    let mut tables_dir = DEFAULT_TABLES_DIR.to_string();
+
+    let mut iter = args.into_iter();
+    while let Some(arg,) = iter.next() {
+        match arg.as_str() {
+            "--grid" => grid_lines = true,
+            "--scale" => {
+                scale = iter
+                    .next()
+                    .and_then(|value| value.parse::<u32>().ok(),)
+                    .filter(|&value| value > 0,)
+                    .ok_or_else(|| "--scale expects a positive number.".to_string(),)?;
+            },
+            "--palette" => {
+                palette_path = iter
+                    .next()
+                    .ok_or_else(|| "--palette expects a path.".to_string(),)?;
+            },
+            "--tables" => {
+                tables_dir = iter
+                    .next()
+                    .ok_or_else(|| "--tables expects a directory.".to_string(),)?;
+            },
+            _ => positional.push(arg,),
+        }
+    }
+
+    let source = match positional.get(1,).map(|kind| kind.as_str(),) {
+        Some("savegame",) if positional.len() == 3 => Source::Savegame(positional[2].clone(),),
+        Some("seed",) if positional.len() == 5 => {
+            Source::Seed {
+                planet_dim: parse_dim(&positional[2],)?,
+                chunk_dim:  parse_dim(&positional[3],)?,
+                seed:       parse_seed(&positional[4],)?,
+            }
+        },
+        _ => return Err("Unexpected arguments.".to_string(),),
+    };
+
+    Ok(Args {
+        output: positional[0].clone(),
+        source,
+        scale,
+        grid_lines,
+        palette_path,
+        tables_dir,
+    },)
+}
@@ -0,0 +1,162 @@
+//! Generates every chunk of a new planet without starting the game.
+//!
+//! Usage: `toppa_pregen <name> <planet_dim> <chunk_dim> <seed> [options]`,
+//! dimensions are given as `<rows>x<columns>`, e.g. `toppa_pregen my_world 8x16 16x32 1234`.
+//! The savegame gets written to `./savegames/<name>`.
+//!
+//! Options:
+//! `--force` overwrites an existing savegame, `--tables <dir>` uses other world generation tables than `Prefabs/world_gen`.
+//! `--skip-unmodified` makes the game write chunks it generates later only once they get modified,
+//! the pregenerated chunks are written anyway.
+use std::{env, fs, io, path::Path, process};
+
+extern crate pretty_env_logger;
+
+extern crate toppa_drill_lib;
+use toppa_drill_lib::{
+    generation, parse_dim, parse_seed, ChunkIndex, GameSessionData, RenderConfig, SaveTransaction,
+    SavegamePaths, DEFAULT_TABLES_DIR,
+};
+
+const USAGE: &str = "Usage: toppa_pregen <name> <planet_dim> <chunk_dim> <seed> [options]\n\
+                     Dimensions are given as <rows>x<columns>, e.g. `toppa_pregen my_world 8x16 16x32 1234`.\n\
+                     Options: --force, --tables <dir>, --skip-unmodified";
+
+/// The parsed command line arguments.
+struct Args {
+    name:            String,
+    planet_dim:      (u64, u64,),
+    chunk_dim:       (u64, u64,),
+    seed:            u64,
+    force:           bool,
+    tables_dir:      String,
+    skip_unmodified: bool,
+}
+
+fn main() {
+    if env::var("RUST_LOG",).is_err() {
+        env::set_var("RUST_LOG", "info",);
+    }
+    pretty_env_logger::init();
+
+    let args = match parse_args(env::args().skip(1,).collect(),) {
+        Ok(args,) => args,
+        Err(e,) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1,);
+        },
+    };
+
+    if let Err(e,) = pregenerate(&args,) {
+        eprintln!("{}", e);
+        process::exit(1,);
+    }
+}
+
+fn pregenerate(args: &Args,) -> Result<(), String,> {
+    if Path::new("./savegames",).join(&args.name,).exists() && !args.force {
+        return Err(format!(
+            "Savegame {:?} already exists, use --force to overwrite it.",
+            args.name
+        ),);
+    }
+
+    // The existing savegame stays untouched until the new one gets committed.
+    let paths = SavegamePaths::init("./", args.name.clone(), false,);
+    fs::create_dir_all(&paths.chunk_dir_path,)
+        .map_err(|e| format!("Could not create {:?}: {}.", paths.chunk_dir_path, e),)?;
+    let tables = generation::WorldGenTables::load_dir(Path::new(&args.tables_dir,),);
+    let mut session_data = GameSessionData::new(
+        args.name.clone(),
+        args.planet_dim,
+        args.chunk_dim,
+        args.seed,
+        &RenderConfig::default(),
+    );
+    session_data.planet.skip_unmodified_chunks = args.skip_unmodified;
+
+    let chunk_count = args.planet_dim.0 * args.planet_dim.1;
+    println!(
+        "Generating {} chunks of {}x{} tiles for {:?} with seed {}.",
+        chunk_count, args.chunk_dim.0, args.chunk_dim.1, args.name, args.seed
+    );
+
+    // All chunks get committed together with the session data, so an interrupted run leaves no half savegame.
+    let mut transaction = SaveTransaction::new(&paths.savegame_dir_path,)
+        .map_err(|e| format!("Could not start writing the savegame: {}.", e),)?;
+    remove_existing_files(&paths, &mut transaction,)
+        .map_err(|e| format!("Could not list the existing savegame: {}.", e),)?;
+    for row in 0 .. args.planet_dim.0 {
+        for col in 0 .. args.planet_dim.1 {
+            let chunk_id = ChunkIndex(row, col,);
+            let tiles = generation::chunk_tiles(&session_data.planet, &tables, chunk_id,);
+            session_data
+                .planet
+                .write_chunk_tiles(&paths.chunk_dir_path, chunk_id, &tiles, &mut transaction,)
+                .map_err(|e| format!("Writing {:?} failed: {:?}.", chunk_id, e),)?;
+        }
+        println!(
+            "{}/{} chunks generated.",
+            (row + 1) * args.planet_dim.1,
+            chunk_count
+        );
+    }
+
+    session_data
+        .save_with(&paths, transaction,)
+        .map_err(|_| "Writing the savegame failed.".to_string(),)?;
+    println!("Savegame written to {:?}.", paths.savegame_dir_path);
+
+    Ok((),)
+}
+
+// Stages the removal of every file of an existing savegame, files of the new one staged afterwards replace them instead.
+fn remove_existing_files(
+    paths: &SavegamePaths,
+    transaction: &mut SaveTransaction,
+) -> io::Result<(),> {
+    for dir_path in [&paths.savegame_dir_path, &paths.chunk_dir_path,].iter() {
+        for entry in fs::read_dir(dir_path,)? {
+            let path = entry?.path();
+            if path.is_file() {
+                transaction.remove(&path,);
+            }
+        }
+    }
+    Ok((),)
+}
+
+fn parse_args(args: Vec<String,>,) -> Result<Args, String,> {
+    let mut positional = Vec::new();
+    let mut force = false;
+    let mut skip_unmodified = false;
+    let mut tables_dir = DEFAULT_TABLES_DIR.to_string();
+
+    let mut iter = args.into_iter();
+    while let Some(arg,) = iter.next() {
+        match arg.as_str() {
+            "--force" => force = true,
+            "--skip-unmodified" => skip_unmodified = true,
+            "--tables" => {
+                tables_dir = iter
+                    .next()
+                    .ok_or_else(|| "--tables expects a directory.".to_string(),)?;
+            },
+            _ => positional.push(arg,),
+        }
+    }
+
+    if positional.len() != 4 {
+        return Err(format!("Expected 4 arguments, got {}.", positional.len()),);
+    }
+
+    Ok(Args {
+        name: positional[0].clone(),
+        planet_dim: parse_dim(&positional[1],)?,
+        chunk_dim: parse_dim(&positional[2],)?,
+        seed: parse_seed(&positional[3],)?,
+        force,
+        tables_dir,
+        skip_unmodified,
+    },)
+}
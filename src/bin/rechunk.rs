@@ -0,0 +1,103 @@
+//! Re-partitions the chunks of an existing savegame into chunks of another dimension, without starting the game.
+//!
+//! Usage: `toppa_rechunk <name> <chunk_dim> [options]`,
+//! dimensions are given as `<rows>x<columns>`, e.g. `toppa_rechunk my_world 32x32`.
+//! The new chunk dimension has to evenly divide the planet's tile count.
+//!
+//! Options:
+//! `--tables <dir>` uses other world generation tables than `Prefabs/world_gen`,
+//! which complete chunks only partially covered by stored ones.
+use std::{env, path::Path, process};
+
+extern crate pretty_env_logger;
+
+extern crate toppa_drill_lib;
+use toppa_drill_lib::{
+    generation::WorldGenTables, parse_dim, GameSessionData, RenderConfig, SavegamePaths,
+    DEFAULT_TABLES_DIR,
+};
+
+const USAGE: &str = "Usage: toppa_rechunk <name> <chunk_dim> [options]\n\
+                     Dimensions are given as <rows>x<columns>, e.g. `toppa_rechunk my_world 32x32`.\n\
+                     Options: --tables <dir>";
+
+/// The parsed command line arguments.
+struct Args {
+    name:       String,
+    chunk_dim:  (u64, u64,),
+    tables_dir: String,
+}
+
+fn main() {
+    if env::var("RUST_LOG",).is_err() {
+        env::set_var("RUST_LOG", "info",);
+    }
+    pretty_env_logger::init();
+
+    let args = match parse_args(env::args().skip(1,).collect(),) {
+        Ok(args,) => args,
+        Err(e,) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1,);
+        },
+    };
+
+    if let Err(e,) = rechunk(&args,) {
+        eprintln!("{}", e);
+        process::exit(1,);
+    }
+}
+
+fn rechunk(args: &Args,) -> Result<(), String,> {
+    if !Path::new("./savegames",)
+        .join(&args.name,)
+        .join("session_data.ron",)
+        .is_file()
+    {
+        return Err(format!("Savegame {:?} does not exist.", args.name),);
+    }
+
+    let paths = SavegamePaths::init("./", args.name.clone(), false,);
+    let tables = WorldGenTables::load_dir(Path::new(&args.tables_dir,),);
+    if GameSessionData::rechunk_savegame(&paths, args.chunk_dim, &tables, &RenderConfig::default(),)
+        .is_err()
+    {
+        return Err(format!(
+            "Re-chunking {:?} failed, the savegame is unchanged.",
+            args.name
+        ),);
+    }
+
+    println!(
+        "Re-chunked {:?} into chunks of {}x{} tiles.",
+        args.name, args.chunk_dim.0, args.chunk_dim.1
+    );
+    Ok((),)
+}
+
+fn parse_args(args: Vec<String,>,) -> Result<Args, String,> {
+    let mut positional = Vec::new();
+    let mut tables_dir = DEFAULT_TABLES_DIR.to_string();
+
+    let mut iter = args.into_iter();
+    while let Some(arg,) = iter.next() {
+        match arg.as_str() {
+            "--tables" => {
+                tables_dir = iter
+                    .next()
+                    .ok_or_else(|| "--tables expects a directory.".to_string(),)?;
+            },
+            _ => positional.push(arg,),
+        }
+    }
+
+    if positional.len() != 2 {
+        return Err(format!("Expected 2 arguments, got {}.", positional.len()),);
+    }
+
+    Ok(Args {
+        name: positional[0].clone(),
+        chunk_dim: parse_dim(&positional[1],)?,
+        tables_dir,
+    },)
+}
@@ -0,0 +1,186 @@
+//! Prints tile statistics of whole planets, to compare ore tables and seeds.
+//!
+//! Usage:
+//! `toppa_report savegame <name> [options]` reads `./savegames/<name>` without changing it,
+//! `toppa_report seed <planet_dim> <chunk_dim> <seed>... [options]` generates one planet per seed,
+//! dimensions are given as `<rows>x<columns>`.
+//!
+//! Options:
+//! `--csv` writes CSV instead of text, `--bands <n>` splits the planet into n depth bands (default: one per chunk row),
+//! `--values <path>` uses other market values than `Prefabs/market_values.ron`,
+//! `--tables <dir>` uses other world generation tables than `Prefabs/world_gen`.
+use std::{
+    env,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+extern crate pretty_env_logger;
+
+extern crate toppa_drill_lib;
+use toppa_drill_lib::{
+    generation::WorldGenTables, load_ron, parse_dim, parse_seed, MarketValues, OverviewMap,
+    PlanetReport, SavegamePaths, DEFAULT_TABLES_DIR,
+};
+
+const USAGE: &str = "Usage: toppa_report savegame <name> [options]\n\
+                     \x20      toppa_report seed <planet_dim> <chunk_dim> <seed>... [options]\n\
+                     Dimensions are given as <rows>x<columns>.\n\
+                     Options: --csv, --bands <n>, --values <path>, --tables <dir>";
+
+/// Where the planets of the report come from.
+enum Source {
+    Savegame(String,),
+    Seeds {
+        seeds:      Vec<u64,>,
+        planet_dim: (u64, u64,),
+        chunk_dim:  (u64, u64,),
+    },
+}
+
+/// The parsed command line arguments.
+struct Args {
+    source:      Source,
+    csv:         bool,
+    band_count:  Option<u64,>,
+    values_path: String,
+    tables_dir:  String,
+}
+
+fn main() {
+    if env::var("RUST_LOG",).is_err() {
+        env::set_var("RUST_LOG", "warn",);
+    }
+    pretty_env_logger::init();
+
+    let args = match parse_args(env::args().skip(1,).collect(),) {
+        Ok(args,) => args,
+        Err(e,) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1,);
+        },
+    };
+
+    if let Err(e,) = report(&args,) {
+        eprintln!("{}", e);
+        process::exit(1,);
+    }
+}
+
+fn report(args: &Args,) -> Result<(), String,> {
+    let values: MarketValues = load_ron(Path::new(&args.values_path,),).map_err(|e| {
+        format!(
+            "Could not load the market values {:?}, {}.",
+            args.values_path, e
+        )
+    },)?;
+    let tables = WorldGenTables::load_dir(Path::new(&args.tables_dir,),);
+
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+    if args.csv {
+        PlanetReport::write_csv_header(&mut writer,).map_err(|e| e.to_string(),)?;
+    }
+
+    let planet_count = match args.source {
+        Source::Savegame(_,) => 1,
+        Source::Seeds {
+            ref seeds, ..
+        } => seeds.len(),
+    };
+
+    // Planets get generated one after the other, so reports of many seeds do not need to fit into memory.
+    for index in 0 .. planet_count {
+        let map = match args.source {
+            Source::Savegame(ref name,) => {
+                // Only read, so the savegame can be inspected while it is being played.
+                let paths = SavegamePaths::existing("./", name,)
+                    .ok_or_else(|| format!("Savegame {:?} does not exist.", name),)?;
+                OverviewMap::from_savegame(&paths, &tables,)
+            },
+            Source::Seeds {
+                ref seeds,
+                planet_dim,
+                chunk_dim,
+            } => OverviewMap::from_seed(seeds[index], planet_dim, chunk_dim, &tables,),
+        }
+        .map_err(|_| "Could not collect the planet's tiles.".to_string(),)?;
+
+        let band_count = args
+            .band_count
+            .unwrap_or(map.tile_count.0 / map.chunk_dim.0.max(1,),);
+        let report = PlanetReport::new(&map, &values, band_count,);
+
+        let result = if args.csv {
+            report.write_csv(&mut writer,)
+        }
+        else if index > 0 {
+            writeln!(writer).and_then(|_| report.write_text(&mut writer,),)
+        }
+        else {
+            report.write_text(&mut writer,)
+        };
+        result.map_err(|e| e.to_string(),)?;
+    }
+
+    Ok((),)
+}
+
+fn parse_args(args: Vec<String,>,) -> Result<Args, String,> {
+    let mut positional = Vec::new();
+    let mut csv = false;
+    let mut band_count = None;
+    let mut values_path = "./Prefabs/market_values.ron".to_string();
+    let mut tables_dir = DEFAULT_TABLES_DIR.to_string();
+
+    let mut iter = args.into_iter();
+    while let Some(arg,) = iter.next() {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            "--bands" => {
+                band_count = Some(
+                    iter.next()
+                        .and_then(|value| value.parse::<u64>().ok(),)
+                        .filter(|&value| value > 0,)
+                        .ok_or_else(|| "--bands expects a positive number.".to_string(),)?,
+                );
+            },
+            "--values" => {
+                values_path = iter
+                    .next()
+                    .ok_or_else(|| "--values expects a path.".to_string(),)?;
+            },
+            "--tables" => {
+                tables_dir = iter
+                    .next()
+                    .ok_or_else(|| "--tables expects a directory.".to_string(),)?;
+            },
+            _ => positional.push(arg,),
+        }
+    }
+
+    let source = match positional.get(0,).map(|kind| kind.as_str(),) {
+        Some("savegame",) if positional.len() == 2 => Source::Savegame(positional[1].clone(),),
+        Some("seed",) if positional.len() >= 4 => {
+            let mut seeds = Vec::with_capacity(positional.len() - 3,);
+            for seed in positional[3 ..].iter() {
+                seeds.push(parse_seed(seed,)?,);
+            }
+            Source::Seeds {
+                seeds,
+                planet_dim: parse_dim(&positional[1],)?,
+                chunk_dim: parse_dim(&positional[2],)?,
+            }
+        },
+        _ => return Err("Unexpected arguments.".to_string(),),
+    };
+
+    Ok(Args {
+        source,
+        csv,
+        band_count,
+        values_path,
+        tables_dir,
+    },)
+}
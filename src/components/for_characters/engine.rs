@@ -11,7 +11,7 @@ use amethyst::{
 /// Burning fuel results in heat generation.
 ///
 /// Can be upgraded.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Engine {
     /// Maximum force in x- and y-direction (right, upwards) of machine's local coordinate system,
     /// specific to this machine, as it acts as an helicopter.
@@ -4,7 +4,7 @@ use amethyst::ecs::prelude::{Component, VecStorage};
 /// Holds `fuel_level`, the current amount of fuel in the tank.
 /// Holds `capacity`, the maximum amount of fuel carryable.
 /// Holds `weight_per_fuel`, the weght of each unit of fuel, updated on every movement or when refilling.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct FuelTank {
     /// Current fuel stored in this tank.
     pub fuel_level: f32,
@@ -4,10 +4,13 @@ mod fuel_tank;
 pub mod player;
 pub use self::{engine::Engine, fuel_tank::FuelTank};
 
-use amethyst::ecs::prelude::{Component, VecStorage};
+use amethyst::{
+    core::specs::saveload::U64Marker,
+    ecs::prelude::{Component, VecStorage},
+};
 
 /// This component is meant for player entities.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PlayerBase {
     pub id: usize,
 }
@@ -16,6 +19,16 @@ impl Component for PlayerBase {
     type Storage = VecStorage<Self,>;
 }
 
+/// Links a part of a ship, like its drill, to the ship it belongs to.
+/// Holds the ship's saveload-marker instead of its entity, so the link survives saving and loading,
+/// where it gets turned into a `Parent`-component again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartOf(pub U64Marker,);
+
+impl Component for PartOf {
+    type Storage = VecStorage<Self,>;
+}
+
 /// This component is meant for npc entities.
 #[derive(Debug, Clone, Copy)]
 pub struct NPCBase {
@@ -43,6 +56,11 @@ impl TagGenerator {
         }
     }
 
+    /// Makes sure the given ID does not get handed out again, e.g. for a player loaded from a savegame.
+    pub fn reserve_player_tag(&mut self, tag: PlayerBase,) {
+        self.player_count = self.player_count.max(tag.id,);
+    }
+
     /// TODO: Use when NPC's introduced
     #[allow(dead_code)]
     pub fn new_npc_tag(&mut self) -> NPCBase {
@@ -11,7 +11,7 @@ use crate::resources::{
 };
 
 /// This component stores the current chunk and tile the player resides on.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub chunk: ChunkIndex,
     pub tile:  TileIndex,
@@ -3,7 +3,7 @@ use amethyst::{
     ecs::prelude::{Component, VecStorage},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dynamics {
     /// The current velocity, consisting of an x-component and a y-component.
     /// The vectors length should be limited by the System using this, e.g. implicitely due to the movement equation.
@@ -2,7 +2,7 @@ use amethyst::ecs::prelude::{Component, VecStorage};
 
 /// A physics-affected entity need to have either friction, air resistance or both,
 /// otherwise it won't move.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PhysicalProperties {
     /// The weight of the entity itself, like a car's empty mass.
     pub mass: f32,
@@ -2,6 +2,7 @@ use amethyst::{
     assets::ProgressCounter,
     core::{
         nalgebra::Vector3,
+        specs::saveload::{MarkedBuilder, U64Marker},
         transform::components::{Parent, Transform},
     },
     ecs::prelude::*,
@@ -15,7 +16,7 @@ use crate::{
     utilities::{load_spritesheet, load_spritesheet_tracked},
 };
 
-use super::PlayerParts;
+use super::{part_of, PlayerParts};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum DrillError {
@@ -27,7 +28,7 @@ pub enum DrillError {
 /// Different types of drill provide different drilling speeds and durability
 /// TODO: Make Drill retractable, retract when it is not used.
 /// TODO: Make Drill animated.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum DrillTypes {
     /// Dummy, if a drill has no implementation yet
     #[allow(dead_code)]
@@ -155,8 +156,9 @@ pub fn new_drill(
         let physical_properties = PhysicalProperties::new(250.0, None, Some(0.8,), None,);
         let mut transform = Transform::default();
         transform.move_global(Vector3::new(22.0, 32.0, -1.0,));
+        let part_of = part_of(world, parent,);
 
-        world
+        let mut builder = world
             .create_entity()
             .with(IsIngameEntity,)
             .with(Parent {
@@ -166,8 +168,13 @@ pub fn new_drill(
             .with(Transparent,)
             .with(sprite_render,)
             .with(physical_properties,)
+            .with(PlayerParts::Drill(drill_type,),)
             .with(Flipped::Vertical) //.... why do i need this?
-            .build();
+            .marked::<U64Marker>();
+        if let Some(part_of,) = part_of {
+            builder = builder.with(part_of,);
+        }
+        builder.build();
 
         Ok((),)
     }
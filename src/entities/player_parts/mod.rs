@@ -4,9 +4,30 @@ mod tracks;
 
 pub use self::{drill::*, player::*, tracks::*};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+use amethyst::{
+    core::specs::saveload::U64Marker,
+    ecs::prelude::{Component, Entity, VecStorage, World},
+};
+
+use crate::components::for_characters::PartOf;
+
+/// Also a component, so the type of every part gets stored in savegames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PlayerParts {
     Ship(player::ShipTypes,),
     Drill(drill::DrillTypes,),
     Tracks,
 }
+
+impl Component for PlayerParts {
+    type Storage = VecStorage<Self,>;
+}
+
+// The link of a new part to its ship, if the ship was marked for saving.
+fn part_of(world: &World, parent: Entity,) -> Option<PartOf,> {
+    world
+        .read_storage::<U64Marker>()
+        .get(parent,)
+        .cloned()
+        .map(PartOf,)
+}
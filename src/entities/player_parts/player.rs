@@ -1,14 +1,20 @@
+use std::collections::HashMap;
+
 use amethyst::{
     assets::ProgressCounter,
-    core::{nalgebra::Vector2, transform::components::Transform},
+    core::{
+        nalgebra::Vector2,
+        specs::saveload::{MarkedBuilder, Marker, U64Marker},
+        transform::components::{Parent, Transform},
+    },
     ecs::prelude::*,
-    renderer::{Transparent,Flipped},
+    renderer::{SpriteRender, Transparent,Flipped},
     shrev::EventChannel,
 };
 
 use crate::{
     components::{
-        for_characters::{player::Position, Engine, FuelTank, TagGenerator},
+        for_characters::{player::Position, Engine, FuelTank, PartOf, PlayerBase, TagGenerator},
         physics::{Dynamics, PhysicalProperties},
         IsIngameEntity,
     },
@@ -32,7 +38,7 @@ pub enum PlayerError {
 }
 
 /// The hull of the ship, provides resistance against forces and impacts.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ShipTypes {
     /// Dummy, if a drill has no implementation yet.
     NotImplemented,
@@ -180,7 +186,9 @@ pub fn new_player(
             .with(dynamics,)
             .with(engine,)
             .with(fuel_tank,)
+            .with(PlayerParts::Ship(ship_type,),)
             .with(Flipped::Vertical) //What's wrong with spritesheet prefabs? Are they only upside down? Is the offset changed?
+            .marked::<U64Marker>()
             .build();
 
         camera::init_camera(world, view_dim, player,);
@@ -194,3 +202,92 @@ pub fn new_player(
         ),)
     }
 }
+
+/// Completes the players and ship parts loaded from a savegame by the
+/// [DePlayersSystem](../../systems/struct.DePlayersSystem.html).
+/// Savegames only contain their state, so this adds their sprites, the camera and the links of the parts to their ship.
+/// Returns the amount of restored players.
+pub fn restore_players(world: &mut World,) -> usize {
+    // Freshly loaded parts are the only ones without a sprite yet.
+    let loaded_parts: Vec<(Entity, PlayerParts, Option<PartOf,>,),> = {
+        let entities = world.entities();
+        let parts = world.read_storage::<PlayerParts>();
+        let part_ofs = world.read_storage::<PartOf>();
+        let sprite_renders = world.read_storage::<SpriteRender>();
+        (&entities, &parts, !&sprite_renders,)
+            .join()
+            .map(|(entity, part, _,)| (entity, *part, part_ofs.get(entity,).cloned(),),)
+            .collect()
+    };
+    let ships: HashMap<u64, Entity,> = {
+        let entities = world.entities();
+        let parts = world.read_storage::<PlayerParts>();
+        let markers = world.read_storage::<U64Marker>();
+        (&entities, &parts, &markers,)
+            .join()
+            .filter(|&(_, part, _,)| match *part {
+                PlayerParts::Ship(_,) => true,
+                _ => false,
+            },)
+            .map(|(entity, _, marker,)| (marker.id(), entity,),)
+            .collect()
+    };
+    let view_dim = world.read_resource::<RenderConfig>().view_dim;
+
+    let mut player_count = 0;
+    for (entity, part, part_of,) in loaded_parts {
+        let sprite_render = match get_spriterender(world, EntitySpriteRender::Player(part,),) {
+            Some(sprite_render,) => sprite_render,
+            None => {
+                error!("| No sprite found for loaded player part {:?}, removing it.", part);
+                let _ = world.delete_entity(entity,);
+                continue;
+            },
+        };
+
+        let result = world
+            .write_storage::<IsIngameEntity>()
+            .insert(entity, IsIngameEntity,)
+            .and_then(|_| world.write_storage::<Transparent>().insert(entity, Transparent,),)
+            .and_then(|_| world.write_storage::<SpriteRender>().insert(entity, sprite_render,),)
+            .and_then(|_| world.write_storage::<Flipped>().insert(entity, Flipped::Vertical,),);
+        if let Err(e,) = result {
+            error!("| Could not restore player part {:?}: {:?}.", part, e);
+            continue;
+        }
+
+        match part {
+            PlayerParts::Ship(_,) => {
+                let player_tag = world.read_storage::<PlayerBase>().get(entity,).cloned();
+                if let Some(player_tag,) = player_tag {
+                    world.write_resource::<TagGenerator>().reserve_player_tag(player_tag,);
+                }
+                camera::init_camera(world, view_dim, entity,);
+                player_count += 1;
+            },
+            _ => {
+                let ship = part_of.and_then(|PartOf(marker,)| ships.get(&marker.id(),).cloned(),);
+                match ship {
+                    Some(ship,) => {
+                        if let Err(e,) = world.write_storage::<Parent>().insert(
+                            entity,
+                            Parent {
+                                entity: ship,
+                            },
+                        ) {
+                            error!("| Could not attach {:?} to its ship: {:?}.", part, e);
+                        }
+                    },
+                    None => {
+                        warn!("| Loaded player part {:?} belongs to no ship, removing it.", part);
+                        let _ = world.delete_entity(entity,);
+                    },
+                }
+            },
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    debug!("| Restored {} players.", player_count);
+    player_count
+}
@@ -2,6 +2,7 @@ use amethyst::{
     assets::ProgressCounter,
     core::{
         nalgebra::Vector3,
+        specs::saveload::{MarkedBuilder, U64Marker},
         transform::components::{Parent, Transform},
     },
     ecs::prelude::*,
@@ -15,7 +16,7 @@ use crate::{
     utilities::{load_spritesheet, load_spritesheet_tracked},
 };
 
-use super::PlayerParts;
+use super::{part_of, PlayerParts};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum TracksError {
@@ -80,8 +81,9 @@ pub fn new_tracks(world: &mut World, parent: Entity,) -> Result<(), EntityError,
         let physical_properties = PhysicalProperties::new(500.0, None, Some(0.3,), None,);
         let mut transform = Transform::default();
         transform.move_global(Vector3::new(0.0, 56.0, 5.0,));
+        let part_of = part_of(world, parent,);
 
-        world
+        let mut builder = world
             .create_entity()
             .with(IsIngameEntity,)
             .with(Parent {
@@ -91,8 +93,13 @@ pub fn new_tracks(world: &mut World, parent: Entity,) -> Result<(), EntityError,
             .with(Transparent,)
             .with(sprite_render,)
             .with(physical_properties,)
+            .with(PlayerParts::Tracks,)
             .with(Flipped::Vertical) //.... why do i need this
-            .build();
+            .marked::<U64Marker>();
+        if let Some(part_of,) = part_of {
+            builder = builder.with(part_of,);
+        }
+        builder.build();
 
         Ok((),)
     }
@@ -4,7 +4,7 @@ use crate::{
     entities::EntitySpriteRender,
     resources::{
         ingame::game_world::{ChunkIndex, TileIndex},
-        GameSprites, ToppaSpriteSheet, RenderConfig,
+        add_spriterender, GameSprites, ToppaSpriteSheet, RenderConfig,
     },
     utilities::{load_spritesheet_tracked, load_spritesheet},
 };
@@ -71,6 +71,35 @@ pub enum TileTypes {
     Sphalerite,
 }
 
+impl TileTypes {
+    /// Whether a drill can break through this tile.
+    /// Any drilling logic has to check this before removing a tile.
+    pub fn is_drillable(self) -> bool {
+        match self {
+            TileTypes::Empty => false,
+            tile_type => !tile_type.is_indestructible(),
+        }
+    }
+
+    /// Whether this tile can never be changed, neither by drills nor by explosions.
+    /// Checked by [`Planet::set_tile`](../../resources/ingame/game_world/struct.Planet.html#method.set_tile).
+    pub fn is_indestructible(self) -> bool {
+        match self {
+            TileTypes::BedRock => true,
+            _ => false,
+        }
+    }
+}
+
+/// Tile types without a sprite of their own yet, and the number of the sprite they borrow.
+/// The treasures borrow sprites of uncommon ores, each a different one, so structures stand out from gold veins.
+const PLACEHOLDER_SPRITES: [(TileTypes, usize); 4] = [
+    (TileTypes::BedRock, 9),
+    (TileTypes::TreasureChest, 7),
+    (TileTypes::Skeleton, 14),
+    (TileTypes::MeteoriteShard, 15),
+];
+
 /// Should run in a State and only once.
 /// Loads the spritesheet from the hardcoded path "Assets/Textures/Ores.png"
 /// and calculates the sprite-vec.
@@ -144,6 +173,15 @@ pub fn prepare_spritesheet(
             }
         }
 
+        for &(tile_type, sprite_number) in PLACEHOLDER_SPRITES.iter() {
+            add_spriterender(
+                EntitySpriteRender::Ore(tile_type,),
+                &mut game_sprites,
+                ss_handle.clone(),
+                sprite_number,
+            );
+        }
+
         {
             let mut sprite_size = (128.0, 128.0);
             let sheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
@@ -219,6 +257,15 @@ pub fn prepare_spritesheet(
             }
         }
 
+        for &(tile_type, sprite_number) in PLACEHOLDER_SPRITES.iter() {
+            add_spriterender(
+                EntitySpriteRender::Ore(tile_type,),
+                &mut game_sprites,
+                ss_handle.clone(),
+                sprite_number,
+            );
+        }
+
         {
             let mut sprite_size = (128.0, 128.0);
             let sheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
@@ -1,4 +1,7 @@
-use crate::resources::ingame::game_world::{ChunkIndex, TileIndex};
+use crate::{
+    entities::tile::TileTypes,
+    resources::ingame::game_world::{ChunkIndex, GlobalTileIndex},
+};
 
 /// TODO: Encompass error messages for the `failed` variants?
 /// Different events regarding [`Chunk`s](struct.Chunk.html)
@@ -20,25 +23,45 @@ pub enum ChunkEvent {
     Unloaded(ChunkIndex,),
     /// Sent if a chunk could not be saved to disk.
     FailedUnload(ChunkIndex,),
+
+    /// Requests all chunks to be re-partitioned into chunks of the given dimension (rows, columns),
+    /// see [`Planet::rechunk`](struct.Planet.html#method.rechunk).
+    /// All loaded chunks get saved and unloaded first.
+    RequestingRechunk((u64, u64,),),
+    /// Sent if the planet was successfully re-chunked, all chunks around the players have to be requested again.
+    Rechunked((u64, u64,),),
+    /// Sent if the planet could not be re-chunked, it keeps its previous chunk dimension.
+    /// The chunks around the players have to be requested again nonetheless.
+    FailedRechunk((u64, u64,),),
 }
 
 /// TODO: Encompass error messages for the `failed` variants?
 /// Different events regarding `Tile`-entities.
+/// Tiles are identified by their [`GlobalTileIndex`](struct.GlobalTileIndex.html), since a `TileIndex` is only unique within its chunk.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum TileEvent {
     /// Every `tile` that has not been visited by a player yet is covered under a `Fog of War`.
     /// This event should be sent when a player has vision on a `tile`.
-    RequestingUncover(TileIndex,),
+    RequestingUncover(GlobalTileIndex,),
     /// Sent if a `tile`'s `Fog of War` has been sucessfully removed.
-    Uncovered(TileIndex,),
+    Uncovered(GlobalTileIndex,),
     /// Sent if a `tile`'s `Fog of War` could not be removed.
-    FailedUncover(TileIndex,),
+    FailedUncover(GlobalTileIndex,),
 
-    /// When a player drills into a `tile` it should be deleted.
-    RequestDeletion(TileIndex,),
+    /// When a player drills into a `tile` it should be deleted, which turns it into an `Empty` tile.
+    /// Tiles that are not [drillable](enum.TileTypes.html#method.is_drillable) cannot be deleted.
+    RequestDeletion(GlobalTileIndex,),
     /// Sent it a `tile` has been successfully deleted.
-    Deleted(TileIndex,),
+    Deleted(GlobalTileIndex,),
     /// Sent if a `tile` could not be deleted.
-    FailedDelete(TileIndex,),
+    FailedDelete(GlobalTileIndex,),
+
+    /// Requests a `tile` to be replaced by one of the given type, e.g. after an explosion.
+    /// Tiles that are [indestructible](enum.TileTypes.html#method.is_indestructible) cannot be replaced.
+    RequestingChange(GlobalTileIndex, TileTypes,),
+    /// Sent if a `tile` has been replaced, carrying its previous and new type.
+    Changed(GlobalTileIndex, TileTypes, TileTypes,),
+    /// Sent if a `tile` could not be replaced by one of the given type, e.g. since its chunk is not loaded.
+    FailedChange(GlobalTileIndex, TileTypes,),
 }
@@ -10,9 +10,6 @@
 
 // TODO: Reduce file operations in HotChunk and Serialize/DeserializeSystem
 
-// TODO: Wrap world in x-direction.
-
-// TODO: Planet surface -> Hills, mountains with empty tiles?
 // TODO: Empty tiles -> Procedural structered look instead of a tile with a hole
 // TODO: Empty Tilemap for procedural cave generation (how to deal with surface and grass?)
 
@@ -48,6 +45,7 @@
 extern crate amethyst;
 #[macro_use]
 extern crate log;
+extern crate png;
 #[macro_use]
 extern crate serde_derive;
 extern crate rand;
@@ -67,8 +65,19 @@ mod utilities;
 // public stuff
 
 pub use self::{
+    resources::{
+        ingame::{
+            game_world::{generation, ChunkIndex, Planet},
+            GameSessionData, SaveTransaction, SavegamePaths,
+        },
+        RenderConfig,
+    },
     states::StartupState,
     toppa_game_data::{ToppaGameData, ToppaGameDataBuilder},
+    utilities::{
+        load_ron, parse_dim, parse_seed, MapPalette, MarketValues, OverviewMap, PlanetReport, RonLoadError,
+        DEFAULT_TABLES_DIR,
+    },
 };
 
 // CONSTANTS
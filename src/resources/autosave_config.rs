@@ -0,0 +1,31 @@
+use std::default::Default;
+
+/// A resource holding the settings of the [AutosaveSystem](../systems/serialization/struct.AutosaveSystem.html).
+/// Loaded from `Prefabs/autosave.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    /// If unset, the game only gets saved when the player presses the save button.
+    pub enabled: bool,
+    /// Seconds of playing time between two autosaves. `0` disables saving on a timer.
+    pub interval: f32,
+    /// The count of autosaves kept next to the manual save, the oldest one gets replaced.
+    pub slot_count: u32,
+    /// Autosaves whenever a player returns to the surface.
+    pub on_surface: bool,
+    /// Seconds that have to pass after an autosave before returning to the surface triggers the next one,
+    /// so flying along the surface does not save over and over.
+    pub surface_cooldown: f32,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        AutosaveConfig {
+            enabled:          true,
+            interval:         300.0,
+            slot_count:       3,
+            on_surface:       true,
+            surface_cooldown: 60.0,
+        }
+    }
+}
@@ -1,18 +1,29 @@
-use std::{fs, path::PathBuf};
+use std::fs;
 
 use ron;
 use serde::{ser::SerializeStruct, Serializer};
 
-use amethyst::ecs::prelude::{Read, Write};
 
-use crate::resources::{ingame::game_world::TileGenerationStorages, RenderConfig};
+use crate::resources::{
+    ingame::game_world::{generation::WorldGenTables, ChunkIndex, TileGenerationStorages},
+    RenderConfig,
+};
 
-use super::{game_world::Planet, SavegamePaths};
+use super::{
+    game_world::Planet,
+    migration::{self, SavegameError, SAVEGAME_VERSION},
+    SaveTransaction, SavegamePaths,
+};
 
 /// Data specific to the current game,
 /// gets loaded or created when the User starts a new game.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameSessionData {
+    /// The version of the savegame format this session was stored with.
+    /// Savegames from before versions existed default to `0`, and get upgraded when loaded.
+    #[serde(default)]
+    pub format_version: u32,
+
     /// The name of this game, also used as the savegame's name, and should be individual each time,
     /// lest another savegame gets overwritten.
     pub game_name: String,
@@ -20,6 +31,7 @@ pub struct GameSessionData {
     /// The planet is basically a container for chunks, which hold the different tiles.
     /// This enables loading and unloading areas in larger bits than single entites, helping with performance,
     /// while also sparing memory.
+    /// Also holds the world seed, which gets serialized alongside the planet dimensions.
     pub planet: Planet,
 }
 
@@ -28,130 +40,244 @@ impl GameSessionData {
         name: String,
         planet_dim: (u64, u64,),
         chunk_dim: (u64, u64,),
+        seed: u64,
         render_config: &RenderConfig,
     ) -> GameSessionData {
         GameSessionData {
-            game_name: name,
-            planet:    Planet::new(planet_dim, chunk_dim, render_config,),
+            format_version: SAVEGAME_VERSION,
+            game_name:      name,
+            planet:         Planet::new(planet_dim, chunk_dim, seed, render_config,),
         }
     }
 
-    /// TODO: Error handling
-    pub fn save(&self, paths: &Read<'_, SavegamePaths,>,) {
+    /// The world seed of the current planet.
+    pub fn seed(&self) -> u64 {
+        self.planet.seed
+    }
+
+    /// Saves the session data and all chunks as one [`SaveTransaction`](struct.SaveTransaction.html),
+    /// if anything fails the previous save stays intact.
+    /// Only chunks that changed since they were loaded or saved the last time get written,
+    /// including the ones unloaded in the meantime.
+    pub fn save(&mut self, paths: &SavegamePaths,) -> Result<(), (),> {
+        let transaction = SaveTransaction::new(&paths.savegame_dir_path,).map_err(|e| {
+            error!("| Could not start saving {:?}: {:?}", self.game_name, e);
+        },)?;
+        self.save_with(paths, transaction,)
+    }
+
+    /// Like [`save`](struct.GameSessionData.html#method.save), but commits the given transaction,
+    /// so other files of the savegame staged in it, like the players, get committed together with the session.
+    pub fn save_with(&mut self, paths: &SavegamePaths, mut transaction: SaveTransaction,) -> Result<(), (),> {
         #[cfg(feature = "debug")]
         debug!("| Starting to serialize savegame.");
 
         #[cfg(feature = "debug")]
         debug!("| Serializing game data.");
 
-        let planet = &self.planet;
+        self.write_session_file(paths, &mut transaction,)?;
 
-        let mut ser_planet = ron::ser::Serializer::new(Some(Default::default(),), true,);
-        {
-            if let Ok(mut serseq,) = ser_planet.serialize_struct("GameSessionData", 2,) {
-                if let Err(e,) = serseq.serialize_field("game_name", &self.game_name,) {
-                    error!("| Error serializing element planet: {:?}", e);
-                }
-                if let Err(e,) = serseq.serialize_field("planet", &self.planet,) {
-                    error!("| Error serializing element planet: {:?}", e);
-                }
-                if let Err(e,) = serseq.end() {
-                    error!("| Error ending serialize for planet: {:?}", e);
-                }
-            }
-            else {
-                error!("| Error starting serialize for planet.");
+        #[cfg(feature = "debug")]
+        debug!("| serializing chunks.");
+
+        let chunk_indices: Vec<ChunkIndex,> =
+            self.planet.iter_chunks().map(|(&chunk_index, _,)| chunk_index,).collect();
+        let mut staged_indices = Vec::with_capacity(chunk_indices.len(),);
+        for chunk_index in chunk_indices {
+            match self
+                .planet
+                .stage_chunk(chunk_index, &paths.chunk_dir_path, &mut transaction,)
+            {
+                Ok(true,) => staged_indices.push(chunk_index,),
+                Ok(false,) => {},
+                Err(_,) => return Err((),),
             }
         }
+        self.planet
+            .stage_unsaved_chunks(&paths.chunk_dir_path, &mut transaction,)
+            .map_err(|_| (),)?;
 
-        if let Err(e,) = fs::write(
-            paths.savegame_file_path.clone(),
-            ser_planet.into_output_string(),
-        ) {
-            error!(
-                "| Writing savegame at '{:?}' threw error: {:?}",
-                paths.savegame_file_path.clone(),
-                e
-            );
+        if let Err(e,) = transaction.commit() {
+            error!("| Committing savegame {:?} failed: {:?}", self.game_name, e);
+            return Err((),);
+        }
+        for chunk_index in staged_indices {
+            self.planet.mark_chunk_saved(chunk_index,);
         }
+        self.planet.clear_unsaved_chunks();
 
         #[cfg(feature = "debug")]
-        debug!("| serializing chunks.");
+        debug!("| Finished serializing savegame.");
+        Ok((),)
+    }
 
-        for (&chunk_index, _,) in planet.iter_chunks() {
-            planet.save_chunk(chunk_index, paths.chunk_dir_path.clone(),);
+    /// Re-partitions the stored chunks of this session into chunks of the new dimension,
+    /// and saves the changed `planet_dim` and `chunk_dim`, see [`Planet::rechunk`](struct.Planet.html#method.rechunk).
+    /// The new chunks and the session get committed as one transaction, so an interrupted re-chunk keeps the old ones.
+    /// All chunks have to be unloaded beforehand, the ones not saved yet get saved as part of the re-chunk.
+    pub fn rechunk(
+        &mut self,
+        paths: &SavegamePaths,
+        new_chunk_dim: (u64, u64,),
+        tables: &WorldGenTables,
+    ) -> Result<(), (),> {
+        let mut transaction = SaveTransaction::new(&paths.savegame_dir_path,).map_err(|e| {
+            error!("| Could not start re-chunking {:?}: {:?}", self.game_name, e);
+        },)?;
+
+        let previous_planet = self.planet.without_chunks();
+        if let Err(e,) = self
+            .planet
+            .rechunk(new_chunk_dim, &paths.chunk_dir_path, tables, &mut transaction,)
+        {
+            error!("| Re-chunking {:?} failed: {:?}.", self.game_name, e);
+            return Err((),);
         }
 
-        #[cfg(feature = "debug")]
-        debug!("| Finished serializing savegame.");
+        // Until the transaction is committed, the stored chunks still have the previous dimension.
+        let result = self.save_with(paths, transaction,);
+        if result.is_err() {
+            self.planet = previous_planet;
+        }
+        result
     }
 
-    /// TODO: Error handling
-    pub fn load(
-        savegame_file_path: PathBuf,
+    /// Re-chunks a savegame without starting it, see [`rechunk`](struct.GameSessionData.html#method.rechunk).
+    pub fn rechunk_savegame(
+        paths: &SavegamePaths,
+        new_chunk_dim: (u64, u64,),
+        tables: &WorldGenTables,
         render_config: &RenderConfig,
-    ) -> Result<GameSessionData, (),> {
+    ) -> Result<(), (),> {
+        let mut session_data = Self::load_savegame(paths, render_config,).map_err(|e| {
+            error!("| {}", e);
+        },)?;
+        session_data.rechunk(paths, new_chunk_dim, tables,)
+    }
+
+    /// Reads the session data of the savegame at the given paths, without changing any of its files,
+    /// e.g. for tools inspecting a savegame while the game might be running.
+    /// Unlike [`load_savegame`](struct.GameSessionData.html#method.load_savegame), savegames that are not at
+    /// [`SAVEGAME_VERSION`](constant.SAVEGAME_VERSION.html) or whose last save was interrupted are refused.
+    pub fn read_savegame(paths: &SavegamePaths,) -> Result<GameSessionData, SavegameError,> {
+        if SaveTransaction::is_interrupted(&paths.savegame_dir_path,) {
+            return Err(SavegameError::UnfinishedSave(paths.savegame_dir_path.clone(),),);
+        }
+
+        let savegame_file_path = paths.savegame_file_path.clone();
+        let session_text = fs::read_to_string(&savegame_file_path,)
+            .map_err(|e| SavegameError::Io(savegame_file_path.clone(), e.to_string(),),)?;
+
+        let found = migration::session_format_version(&session_text,)
+            .map_err(|e| SavegameError::Corrupt(savegame_file_path.clone(), e,),)?;
+        if found > SAVEGAME_VERSION {
+            return Err(SavegameError::UnsupportedVersion {
+                found,
+                supported: SAVEGAME_VERSION,
+            },);
+        }
+        if found < SAVEGAME_VERSION {
+            return Err(SavegameError::Outdated {
+                found,
+                current: SAVEGAME_VERSION,
+            },);
+        }
+
+        ron::de::from_str(&session_text,)
+            .map_err(|e| SavegameError::Corrupt(savegame_file_path.clone(), format!("{:?}", e),),)
+    }
+
+    /// Loads the savegame at the given paths.
+    /// Savegames of older versions get upgraded and stored again, see [`SAVEGAME_VERSION`](constant.SAVEGAME_VERSION.html),
+    /// savegames of newer versions are refused.
+    pub fn load_savegame(
+        paths: &SavegamePaths,
+        _render_config: &RenderConfig,
+    ) -> Result<GameSessionData, SavegameError,> {
         #[cfg(feature = "debug")]
         debug!("| Starting to deserialize savegame.");
 
+        // A save interrupted by a crash gets finished or discarded first.
+        SaveTransaction::recover(&paths.savegame_dir_path,)
+            .map_err(|e| SavegameError::Io(paths.savegame_dir_path.clone(), e.to_string(),),)?;
+
+        let savegame_file_path = paths.savegame_file_path.clone();
+        let mut session_text = fs::read_to_string(&savegame_file_path,)
+            .map_err(|e| SavegameError::Io(savegame_file_path.clone(), e.to_string(),),)?;
+
+        let found_version = migration::migrate_savegame(&mut session_text, paths,)?;
+
         #[cfg(feature = "debug")]
         debug!("| Deserializing game data.");
 
-        let file = match fs::File::open(&savegame_file_path,) {
-            Ok(rv,) => rv,
-            Err(e,) => {
-                error!(
-                    "| Could not open {:?}: {:?}.",
-                    savegame_file_path.clone(),
-                    e
-                );
-                return Err((),);
-            },
-        };
-        /*
-        let dummy_data: DummyStructOne = match ron::de::from_reader(&file) {
-            Ok(data) => data,
-            Err(e) => {
-                error!(
-                    "| Error deserializing {:?}: {:?}.",
-                    savegame_file_path.clone(),
-                    e
+        let mut session_data: GameSessionData = ron::de::from_str(&session_text,)
+            .map_err(|e| SavegameError::Corrupt(savegame_file_path.clone(), format!("{:?}", e),),)?;
+
+        if found_version < SAVEGAME_VERSION {
+            // If this fails, the migrations run again on the next load.
+            session_data.format_version = SAVEGAME_VERSION;
+            let stored = SaveTransaction::new(&paths.savegame_dir_path,)
+                .map_err(|_| (),)
+                .and_then(|mut transaction| {
+                    session_data.write_session_file(paths, &mut transaction,)?;
+                    transaction.commit().map_err(|_| (),)
+                },);
+            if stored.is_err() {
+                warn!(
+                    "| Could not store the upgraded session data of {:?}.",
+                    session_data.game_name
                 );
-                return Err(());
-            }
-        };
-        
-        #[cfg(feature = "debug")]
-        debug!("| Finished deserializing savegame.");
-        
-        Ok(
-            GameSessionData{
-                game_name: dummy_data.game_name,
-                planet: Planet::new(
-                    dummy_data.planet.planet_dim,
-                    dummy_data.planet.chunk_dim,
-                    render_config
-                )
             }
-        )
-        */
-        let session_data: GameSessionData = match ron::de::from_reader(&file,) {
-            Ok(data,) => data,
-            Err(e,) => {
-                error!(
-                    "| Error deserializing {:?}: {:?}.",
-                    savegame_file_path.clone(),
-                    e
-                );
-                return Err((),);
-            },
-        };
+        }
 
         #[cfg(feature = "debug")]
         debug!("| Finished deserializing savegame.");
 
         Ok(session_data,)
     }
+
+    /// The content of `session_data.ron`, without any chunks.
+    /// Also used by the [`AutosaveSystem`](../../systems/serialization/struct.AutosaveSystem.html),
+    /// which writes it on another thread.
+    pub fn session_file_text(&self) -> String {
+        let mut ser_planet = ron::ser::Serializer::new(Some(Default::default(),), true,);
+        {
+            if let Ok(mut serseq,) = ser_planet.serialize_struct("GameSessionData", 3,) {
+                // Written first, so it can be read without deserializing the rest.
+                if let Err(e,) = serseq.serialize_field("format_version", &self.format_version,) {
+                    error!("| Error serializing element format_version: {:?}", e);
+                }
+                if let Err(e,) = serseq.serialize_field("game_name", &self.game_name,) {
+                    error!("| Error serializing element planet: {:?}", e);
+                }
+                if let Err(e,) = serseq.serialize_field("planet", &self.planet,) {
+                    error!("| Error serializing element planet: {:?}", e);
+                }
+                if let Err(e,) = serseq.end() {
+                    error!("| Error ending serialize for planet: {:?}", e);
+                }
+            }
+            else {
+                error!("| Error starting serialize for planet.");
+            }
+        }
+
+        ser_planet.into_output_string()
+    }
+
+    // Stages `session_data.ron`, without any chunks.
+    fn write_session_file(&self, paths: &SavegamePaths, transaction: &mut SaveTransaction,) -> Result<(), (),> {
+        if let Err(e,) = transaction.write(&paths.savegame_file_path, self.session_file_text().as_bytes(),) {
+            error!(
+                "| Writing savegame at '{:?}' threw error: {:?}",
+                paths.savegame_file_path.clone(),
+                e
+            );
+            return Err((),);
+        }
+
+        Ok((),)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,422 @@
+//! The compact binary format chunks get stored in.
+//!
+//! Every chunk file starts with a header, all numbers are little endian:
+//!
+//! | bytes | content                                  |
+//! |-------|------------------------------------------|
+//! | 4     | magic number `TDCH`                      |
+//! | 2     | format version                           |
+//! | 4 + 4 | chunk dim (rows, columns)                |
+//! | 4     | CRC-32 checksum of the body              |
+//! | 4     | length of the body in bytes              |
+//!
+//! The body starts with a palette of all tile types found in the chunk, each stored by its name,
+//! so reordering `TileTypes` does not break existing savegames.
+//! It is followed by the tiles row by row, run-length-encoded as pairs of (run length as varint, palette index).
+use std::collections::BTreeMap;
+
+use ron;
+
+use crate::entities::tile::TileTypes;
+
+use super::TileIndex;
+
+/// The version of the chunk format written by [`encode`](fn.encode.html).
+pub const FORMAT_VERSION: u16 = 1;
+/// The extension of chunk files in this format.
+pub const EXTENSION: &str = "chunk";
+
+const MAGIC: &[u8; 4] = b"TDCH";
+const HEADER_LEN: usize = 22;
+// Palette index of tiles missing in a chunk.
+const NO_TILE: u8 = 0xFF;
+
+/// Problems reading or writing the binary chunk format.
+#[derive(PartialEq, Eq, Clone, Debug,)]
+pub enum ChunkFormatError {
+    /// The data does not start with the magic number, so it is no chunk file.
+    InvalidMagic,
+    /// The chunk was written by a newer version of the game.
+    UnsupportedVersion(u16,),
+    /// The chunk dimension stored in the header differs from the planet's.
+    DimensionMismatch {
+        stored:   (u64, u64,),
+        expected: (u64, u64,),
+    },
+    /// The chunk dimension is too large to be stored.
+    InvalidDimension((u64, u64,),),
+    /// The body does not match its checksum.
+    ChecksumMismatch,
+    /// The data ended before the chunk was complete.
+    Truncated,
+    /// The body contains more tiles than the chunk dimension allows, or an invalid palette index.
+    InvalidBody,
+    /// A chunk contains more tile types than the palette can hold.
+    TooManyTileTypes,
+    /// The palette contains a name that is no `TileTypes`.
+    UnknownTileType(String,),
+}
+
+/// Encodes the tiles of a chunk of the given dimension, including the header.
+pub fn encode(
+    chunk_dim: (u64, u64,),
+    tiles: &BTreeMap<TileIndex, TileTypes,>,
+) -> Result<Vec<u8,>, ChunkFormatError,> {
+    if chunk_dim.0 > u64::from(u32::max_value(),) || chunk_dim.1 > u64::from(u32::max_value(),) {
+        return Err(ChunkFormatError::InvalidDimension(chunk_dim,),);
+    }
+
+    let mut palette: Vec<TileTypes,> = Vec::new();
+    let mut grid: Vec<u8,> = Vec::with_capacity((chunk_dim.0 * chunk_dim.1) as usize,);
+    for y in 0 .. chunk_dim.0 {
+        for x in 0 .. chunk_dim.1 {
+            let index = match tiles.get(&TileIndex(y, x,),) {
+                Some(&tile_type,) => {
+                    match palette.iter().position(|&entry| entry == tile_type,) {
+                        Some(index,) => index,
+                        None => {
+                            palette.push(tile_type,);
+                            palette.len() - 1
+                        },
+                    }
+                },
+                None => NO_TILE as usize,
+            };
+            if palette.len() > NO_TILE as usize {
+                return Err(ChunkFormatError::TooManyTileTypes,);
+            }
+            grid.push(index as u8,);
+        }
+    }
+
+    let mut body = Vec::with_capacity(64,);
+    body.push(palette.len() as u8,);
+    for tile_type in palette.iter() {
+        let name = match ron::ser::to_string(tile_type,) {
+            Ok(name,) => name,
+            Err(_,) => {
+                return Err(ChunkFormatError::UnknownTileType(format!(
+                    "{:?}",
+                    tile_type
+                ),),)
+            },
+        };
+        body.push(name.len() as u8,);
+        body.extend_from_slice(name.as_bytes(),);
+    }
+
+    let mut tiles_iter = grid.iter().peekable();
+    while let Some(&index,) = tiles_iter.next() {
+        let mut run: u64 = 1;
+        while tiles_iter.peek() == Some(&&index,) {
+            tiles_iter.next();
+            run += 1;
+        }
+        write_varint(&mut body, run,);
+        body.push(index,);
+    }
+
+    let mut rv = Vec::with_capacity(HEADER_LEN + body.len(),);
+    rv.extend_from_slice(MAGIC,);
+    write_le(&mut rv, u64::from(FORMAT_VERSION,), 2,);
+    write_le(&mut rv, chunk_dim.0, 4,);
+    write_le(&mut rv, chunk_dim.1, 4,);
+    write_le(&mut rv, u64::from(crc32(&body,),), 4,);
+    write_le(&mut rv, body.len() as u64, 4,);
+    rv.extend_from_slice(&body,);
+
+    Ok(rv,)
+}
+
+/// Decodes a chunk written by [`encode`](fn.encode.html).
+/// Fails if the stored chunk dimension differs from the expected one, e.g. since the planet was re-chunked.
+pub fn decode(
+    bytes: &[u8],
+    expected_chunk_dim: (u64, u64,),
+) -> Result<BTreeMap<TileIndex, TileTypes,>, ChunkFormatError,> {
+    let mut reader = ByteReader {
+        bytes,
+        pos: 0,
+    };
+
+    if reader.take(MAGIC.len(),)? != MAGIC {
+        return Err(ChunkFormatError::InvalidMagic,);
+    }
+    let version = reader.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(ChunkFormatError::UnsupportedVersion(version,),);
+    }
+    let chunk_dim = (u64::from(reader.u32()?,), u64::from(reader.u32()?,),);
+    if chunk_dim != expected_chunk_dim {
+        return Err(ChunkFormatError::DimensionMismatch {
+            stored:   chunk_dim,
+            expected: expected_chunk_dim,
+        },);
+    }
+    let checksum = reader.u32()?;
+    let body_len = reader.u32()? as usize;
+    let body = reader.take(body_len,)?;
+    if crc32(body,) != checksum {
+        return Err(ChunkFormatError::ChecksumMismatch,);
+    }
+
+    let mut reader = ByteReader {
+        bytes: body,
+        pos:   0,
+    };
+    let palette_len = reader.u8()?;
+    let mut palette = Vec::with_capacity(palette_len as usize,);
+    for _ in 0 .. palette_len {
+        let name_len = reader.u8()? as usize;
+        let name = String::from_utf8_lossy(reader.take(name_len,)?,).into_owned();
+        match ron::de::from_str::<TileTypes,>(&name,) {
+            Ok(tile_type,) => palette.push(tile_type,),
+            Err(_,) => return Err(ChunkFormatError::UnknownTileType(name,),),
+        }
+    }
+
+    let tile_count = chunk_dim.0 * chunk_dim.1;
+    let mut rv = BTreeMap::new();
+    let mut tile_number: u64 = 0;
+    while tile_number < tile_count {
+        let run = reader.varint()?;
+        let index = reader.u8()?;
+        if run == 0 || run > tile_count - tile_number {
+            return Err(ChunkFormatError::InvalidBody,);
+        }
+        let tile_type = match palette.get(index as usize,) {
+            Some(&tile_type,) => Some(tile_type,),
+            None if index == NO_TILE => None,
+            None => return Err(ChunkFormatError::InvalidBody,),
+        };
+
+        for _ in 0 .. run {
+            if let Some(tile_type,) = tile_type {
+                rv.insert(
+                    TileIndex(tile_number / chunk_dim.1, tile_number % chunk_dim.1,),
+                    tile_type,
+                );
+            }
+            tile_number += 1;
+        }
+    }
+    if reader.pos != body.len() {
+        return Err(ChunkFormatError::InvalidBody,);
+    }
+
+    Ok(rv,)
+}
+
+/// The format version of an encoded chunk, reading only its header.
+pub fn format_version(bytes: &[u8],) -> Result<u16, ChunkFormatError,> {
+    let mut reader = ByteReader {
+        bytes,
+        pos: 0,
+    };
+    if reader.take(MAGIC.len(),)? != MAGIC {
+        return Err(ChunkFormatError::InvalidMagic,);
+    }
+    reader.u16()
+}
+
+/// The CRC-32 (IEEE) checksum of the given bytes.
+pub fn crc32(bytes: &[u8],) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte,);
+        for _ in 0 .. 8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// Writes the lowest `byte_count` bytes of the value, little endian.
+fn write_le(bytes: &mut Vec<u8,>, value: u64, byte_count: usize,) {
+    for i in 0 .. byte_count {
+        bytes.push((value >> (8 * i)) as u8,);
+    }
+}
+
+// Writes the value as LEB128, 7 bits per byte.
+fn write_varint(bytes: &mut Vec<u8,>, mut value: u64,) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte,);
+            return;
+        }
+        bytes.push(byte | 0x80,);
+    }
+}
+
+struct ByteReader<'a,> {
+    bytes: &'a [u8],
+    pos:   usize,
+}
+
+impl<'a,> ByteReader<'a,> {
+    fn take(&mut self, count: usize,) -> Result<&'a [u8], ChunkFormatError,> {
+        if self.bytes.len() - self.pos < count {
+            return Err(ChunkFormatError::Truncated,);
+        }
+        let rv = &self.bytes[self.pos .. self.pos + count];
+        self.pos += count;
+        Ok(rv,)
+    }
+
+    fn u8(&mut self,) -> Result<u8, ChunkFormatError,> {
+        Ok(self.take(1,)?[0],)
+    }
+
+    fn u16(&mut self,) -> Result<u16, ChunkFormatError,> {
+        let bytes = self.take(2,)?;
+        Ok(u16::from(bytes[0],) | u16::from(bytes[1],) << 8,)
+    }
+
+    fn u32(&mut self,) -> Result<u32, ChunkFormatError,> {
+        let bytes = self.take(4,)?;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0u32, |acc, &byte| acc << 8 | u32::from(byte,),),)
+    }
+
+    fn varint(&mut self,) -> Result<u64, ChunkFormatError,> {
+        let mut rv: u64 = 0;
+        for shift in (0 .. 64).step_by(7,) {
+            let byte = self.u8()?;
+            rv |= u64::from(byte & 0x7F,) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(rv,);
+            }
+        }
+        Err(ChunkFormatError::InvalidBody,)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 3x4 chunk with two tile types and two missing tiles.
+    fn sample_tiles() -> BTreeMap<TileIndex, TileTypes,> {
+        let mut tiles = BTreeMap::new();
+        for y in 0 .. 3 {
+            for x in 0 .. 4 {
+                let tile_type = if y == 0 {
+                    TileTypes::Dirt
+                }
+                else {
+                    TileTypes::Rock
+                };
+                tiles.insert(TileIndex(y, x,), tile_type,);
+            }
+        }
+        tiles.remove(&TileIndex(1, 1,),);
+        tiles.remove(&TileIndex(2, 3,),);
+        tiles
+    }
+
+    // A chunk with the given body and a matching header.
+    fn with_body(chunk_dim: (u64, u64,), body: &[u8],) -> Vec<u8,> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC,);
+        write_le(&mut bytes, u64::from(FORMAT_VERSION,), 2,);
+        write_le(&mut bytes, chunk_dim.0, 4,);
+        write_le(&mut bytes, chunk_dim.1, 4,);
+        write_le(&mut bytes, u64::from(crc32(body,),), 4,);
+        write_le(&mut bytes, body.len() as u64, 4,);
+        bytes.extend_from_slice(body,);
+        bytes
+    }
+
+    #[test]
+    fn roundtrip_keeps_missing_tiles_missing() {
+        let tiles = sample_tiles();
+        let bytes = encode((3, 4,), &tiles,).unwrap();
+        assert_eq!(format_version(&bytes), Ok(FORMAT_VERSION));
+        assert_eq!(decode(&bytes, (3, 4,)), Ok(tiles));
+    }
+
+    #[test]
+    fn roundtrip_of_empty_chunk() {
+        let tiles = BTreeMap::new();
+        let bytes = encode((2, 2,), &tiles,).unwrap();
+        assert_eq!(decode(&bytes, (2, 2,)), Ok(tiles));
+    }
+
+    #[test]
+    fn refuses_other_dimension() {
+        let bytes = encode((3, 4,), &sample_tiles(),).unwrap();
+        assert_eq!(
+            decode(&bytes, (4, 3,)),
+            Err(ChunkFormatError::DimensionMismatch {
+                stored:   (3, 4,),
+                expected: (4, 3,),
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_damaged_body() {
+        let mut bytes = encode((3, 4,), &sample_tiles(),).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        assert_eq!(
+            decode(&bytes, (3, 4,)),
+            Err(ChunkFormatError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn refuses_truncated_chunk() {
+        let bytes = encode((3, 4,), &sample_tiles(),).unwrap();
+        for len in [0, 3, HEADER_LEN - 1, bytes.len() - 1,].iter() {
+            assert_eq!(
+                decode(&bytes[.. *len], (3, 4,)),
+                Err(ChunkFormatError::Truncated)
+            );
+        }
+    }
+
+    #[test]
+    fn refuses_run_longer_than_chunk() {
+        let mut body = vec![1, 4];
+        body.extend_from_slice(b"Dirt",);
+        write_varint(&mut body, 5,);
+        body.push(0,);
+        assert_eq!(
+            decode(&with_body((2, 2,), &body,), (2, 2,)),
+            Err(ChunkFormatError::InvalidBody)
+        );
+    }
+
+    #[test]
+    fn refuses_newer_version() {
+        let mut bytes = encode((3, 4,), &sample_tiles(),).unwrap();
+        let newer = FORMAT_VERSION + 1;
+        bytes[4] = newer as u8;
+        bytes[5] = (newer >> 8) as u8;
+        assert_eq!(format_version(&bytes), Ok(newer));
+        assert_eq!(
+            decode(&bytes, (3, 4,)),
+            Err(ChunkFormatError::UnsupportedVersion(newer))
+        );
+    }
+
+    #[test]
+    fn refuses_other_files() {
+        let mut bytes = encode((3, 4,), &sample_tiles(),).unwrap();
+        bytes[0] = b'X';
+        assert_eq!(decode(&bytes, (3, 4,)), Err(ChunkFormatError::InvalidMagic));
+    }
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+}
@@ -0,0 +1,122 @@
+use rand::Rng;
+
+use crate::entities::tile::TileTypes;
+
+use super::{hash_rng, noise, position_hash, SALT_BIOMES};
+
+/// A horizontal region of a planet, which changes the mix of tiles found in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Biome {
+    pub name: String,
+    /// How often this biome gets chosen compared to the others.
+    pub frequency: f32,
+    /// Multipliers for the weights of the [`OreTable`](struct.OreTable.html),
+    /// tile types not listed here keep their weight.
+    pub ore_multipliers: Vec<(TileTypes, f32)>,
+}
+
+impl Biome {
+    /// The multiplier for the weight of the given tile type in this biome.
+    pub fn multiplier(&self, tile: TileTypes) -> f32 {
+        self.ore_multipliers
+            .iter()
+            .find(|(multiplied_tile, _)| *multiplied_tile == tile)
+            .map(|(_, multiplier)| multiplier.max(0.0))
+            .unwrap_or(1.0)
+    }
+}
+
+/// All biomes a planet may consist of, and how wide they get.
+/// Loaded from `Prefabs/world_gen/biomes.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiomeTable {
+    /// The approximate width of a biome in tiles.
+    /// Gets adjusted, so a whole number of biomes fits around the planet.
+    pub width: u64,
+    /// The part of a biome's width, which gets blended with its neighbours, split evenly between both borders.
+    pub blend: f32,
+    pub biomes: Vec<Biome>,
+}
+
+impl Default for BiomeTable {
+    fn default() -> Self {
+        BiomeTable {
+            width: 64,
+            blend: 0.0,
+            biomes: Vec::new(),
+        }
+    }
+}
+
+/// The biomes at a single column of the planet.
+/// Close to a biome border the neighbouring biome gets mixed in.
+#[derive(Debug, Clone, Copy)]
+pub struct BiomeBlend<'a> {
+    pub primary: Option<&'a Biome>,
+    pub secondary: Option<&'a Biome>,
+    /// How much of the `secondary` biome gets mixed in, `0.0 ..= 0.5`.
+    pub secondary_share: f32,
+}
+
+impl<'a> BiomeBlend<'a> {
+    /// The blended multiplier for the weight of the given tile type.
+    pub fn multiplier(&self, tile: TileTypes) -> f32 {
+        let primary = self.primary.map_or(1.0, |biome| biome.multiplier(tile));
+        let secondary = self.secondary.map_or(primary, |biome| biome.multiplier(tile));
+        primary + (secondary - primary) * self.secondary_share
+    }
+}
+
+impl BiomeTable {
+    /// The count of biomes around a planet `tile_count_x` tiles wide.
+    pub fn biome_count(&self, tile_count_x: u64) -> u64 {
+        noise::cells_around(tile_count_x, self.width.max(1) as f64)
+    }
+
+    /// The biome of the given region, counted from the left edge of the planet.
+    /// Only depends on the seed, so every region keeps its biome.
+    pub fn region_biome(&self, seed: u64, region: u64) -> Option<&Biome> {
+        let total_frequency: f32 = self.biomes.iter().map(|biome| biome.frequency.max(0.0)).sum();
+        if total_frequency <= 0.0 {
+            return None;
+        }
+
+        let mut rng = hash_rng(position_hash(seed, (0, region), SALT_BIOMES));
+        let mut roll = rng.gen_range(0.0, total_frequency);
+        for biome in self.biomes.iter() {
+            let frequency = biome.frequency.max(0.0);
+            if roll < frequency {
+                return Some(biome);
+            }
+            roll -= frequency;
+        }
+
+        self.biomes.last()
+    }
+
+    /// The biomes at the given column of a planet `tile_count_x` tiles wide.
+    /// Biomes wrap around the planet just like its tiles.
+    pub fn blend_at(&self, seed: u64, column: u64, tile_count_x: u64) -> BiomeBlend<'_> {
+        let biome_count = self.biome_count(tile_count_x);
+        let pos = (column as f32 + 0.5) * biome_count as f32 / tile_count_x.max(1) as f32;
+        let region = (pos.floor() as u64) % biome_count;
+        let fraction = pos - pos.floor();
+        let half_blend = 0.5 * self.blend.max(0.0).min(1.0);
+
+        let (neighbour, secondary_share) = if half_blend > 0.0 && fraction < half_blend {
+            (region + biome_count - 1, 0.5 * (1.0 - fraction / half_blend))
+        }
+        else if half_blend > 0.0 && fraction > 1.0 - half_blend {
+            (region + 1, 0.5 * (1.0 - (1.0 - fraction) / half_blend))
+        }
+        else {
+            (region, 0.0)
+        };
+
+        BiomeBlend {
+            primary: self.region_biome(seed, region),
+            secondary: self.region_biome(seed, neighbour % biome_count),
+            secondary_share,
+        }
+    }
+}
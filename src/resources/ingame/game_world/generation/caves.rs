@@ -0,0 +1,54 @@
+use super::{noise, SALT_CAVERNS, SALT_TUNNELS};
+
+/// Relative depth below which caves slowly fade out, so the surface does not look like swiss cheese.
+const SURFACE_FADE_DEPTH: f64 = 0.08;
+
+/// Size of a tunnel noise cell in tiles (rows, columns), stretched horizontally for longer tunnels.
+const TUNNEL_CELL: (f64, f64) = (10.0, 28.0);
+/// Width of the tunnels in noise units at the surface and at the bottom of the planet.
+const TUNNEL_WIDTH: (f64, f64) = (0.025, 0.085);
+
+/// Size of a cavern noise cell in tiles.
+const CAVERN_CELL: f64 = 14.0;
+/// Noise value a tile needs to exceed to become part of a cavern, at the surface and at the bottom of the planet.
+const CAVERN_THRESHOLD: (f64, f64) = (0.62, 0.32);
+
+/// Checks whether the tile at the given global position (rows, columns) is carved out by a cave.
+///
+/// Tunnels follow the zero-crossings of a noise field, which form long connected lines,
+/// caverns are the peaks of a second noise field. Both get denser with the relative depth.
+/// Since only the global position is used, caves continue seamlessly across chunk borders,
+/// as well as around the planet, which is `tile_count_x` tiles wide.
+pub fn is_cave(seed: u64, global_pos: (u64, u64), rel_depth: f32, tile_count_x: u64) -> bool {
+    let y = global_pos.0 as f64;
+    let x = global_pos.1 as f64 / tile_count_x.max(1) as f64;
+    let depth = f64::from(rel_depth).max(0.0).min(1.0);
+    let surface_fade = (depth / SURFACE_FADE_DEPTH).min(1.0);
+
+    let tunnel_cells = noise::cells_around(tile_count_x, TUNNEL_CELL.1);
+    let tunnel = noise::fractal(
+        seed,
+        SALT_TUNNELS,
+        x * tunnel_cells as f64,
+        y / TUNNEL_CELL.0,
+        2,
+        tunnel_cells,
+    );
+    let tunnel_width = TUNNEL_WIDTH.0 + (TUNNEL_WIDTH.1 - TUNNEL_WIDTH.0) * depth;
+    if tunnel.abs() < tunnel_width * surface_fade {
+        return true;
+    }
+
+    let cavern_cells = noise::cells_around(tile_count_x, CAVERN_CELL);
+    let cavern = noise::fractal(
+        seed,
+        SALT_CAVERNS,
+        x * cavern_cells as f64,
+        y / CAVERN_CELL,
+        3,
+        cavern_cells,
+    );
+    let cavern_threshold =
+        CAVERN_THRESHOLD.0 + (CAVERN_THRESHOLD.1 - CAVERN_THRESHOLD.0) * depth;
+    cavern * surface_fade > cavern_threshold
+}
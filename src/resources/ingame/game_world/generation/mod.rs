@@ -0,0 +1,179 @@
+//! Everything needed to procedurally determine the `TileTypes` of a planet.
+//! All functions in here are pure functions of the world seed and the position of a tile,
+//! so the same seed always results in the same planet, no matter in which order chunks get generated.
+mod biomes;
+mod caves;
+mod ore_table;
+mod planet_core;
+mod seed;
+mod structures;
+mod surface;
+mod veins;
+
+pub mod noise;
+
+pub use self::{
+    biomes::*,
+    caves::is_cave,
+    ore_table::*,
+    planet_core::CoreSettings,
+    seed::*,
+    structures::{StructureTable, StructureTemplate},
+    surface::{surface_row, topsoil_depth},
+    veins::{vein_ore, VeinSettings, VeinShape},
+};
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    entities::tile::TileTypes,
+    resources::ingame::game_world::{ChunkIndex, Planet, TileIndex},
+    utilities::load_ron,
+};
+
+/// This is a resource.
+/// All data-driven tables used to generate a planet.
+/// Gets (re-)loaded whenever a game session starts, so they can be rebalanced without recompiling.
+#[derive(Debug, Clone, Default)]
+pub struct WorldGenTables {
+    /// Which tiles appear at which depth, and how often.
+    pub ores: OreTable,
+    /// How the mix of tiles changes along the planet's width.
+    pub biomes: BiomeTable,
+    /// The bedrock and core at the bottom of the planet.
+    pub core: CoreSettings,
+    /// Pre-authored structures, like mine shafts or treasure vaults.
+    pub structures: StructureTable,
+}
+
+impl WorldGenTables {
+    /// Loads all tables from `Prefabs/world_gen` below the given base path.
+    /// Tables that cannot be loaded fall back to their defaults.
+    pub fn load(base_path: &Path) -> WorldGenTables {
+        Self::load_dir(&base_path.join("Prefabs").join("world_gen"))
+    }
+
+    /// Loads all tables from the given directory, e.g. a copy of `Prefabs/world_gen` being rebalanced.
+    /// Tables that cannot be loaded fall back to their defaults.
+    pub fn load_dir(dir_path: &Path) -> WorldGenTables {
+        let ores = load_ron(&dir_path.join("ore_table.ron")).unwrap_or_else(|e| {
+            warn!(
+                "| Ore table could not be loaded, {}. Every tile will be {:?}.",
+                e,
+                OreTable::default().fallback
+            );
+            OreTable::default()
+        });
+        let biomes = load_ron(&dir_path.join("biomes.ron")).unwrap_or_else(|e| {
+            warn!("| Biome table could not be loaded, {}. The planet will have no biomes.", e);
+            BiomeTable::default()
+        });
+        let core = load_ron(&dir_path.join("core.ron")).unwrap_or_else(|e| {
+            warn!("| Core settings could not be loaded, {}. Falling back to the defaults.", e);
+            CoreSettings::default()
+        });
+        let structures = load_ron(&dir_path.join("structures.ron")).unwrap_or_else(|e| {
+            warn!("| Structure table could not be loaded, {}. No structures will be placed.", e);
+            StructureTable::default()
+        });
+
+        WorldGenTables {
+            ores,
+            biomes,
+            core,
+            structures,
+        }
+    }
+}
+
+/// Salts to keep the random numbers of the different generation passes independent of each other.
+/// Noise functions use `salt + octave`, so keep some distance between them.
+const SALT_ORES: u64 = 0;
+const SALT_TUNNELS: u64 = 0x100;
+const SALT_CAVERNS: u64 = 0x200;
+const SALT_VEINS: u64 = 0x300;
+const SALT_VEIN_EDGES: u64 = 0x301;
+const SALT_SURFACE: u64 = 0x400;
+const SALT_TOPSOIL: u64 = 0x410;
+const SALT_BIOMES: u64 = 0x500;
+const SALT_BEDROCK: u64 = 0x600;
+const SALT_CORE: u64 = 0x610;
+const SALT_STRUCTURES: u64 = 0x700;
+
+/// Determines the `TileTypes` of the given tile, based on its depth and the biome it lies in.
+/// The bottom of the planet is made up of the core, and capped with bedrock.
+/// Structures get stamped on top of everything but the sky, topsoil and bedrock.
+/// Only depends on the planet's seed and dimensions, as well as the given tables.
+pub fn tile_type(
+    planet: &Planet,
+    tables: &WorldGenTables,
+    chunk_id: ChunkIndex,
+    tile_id: TileIndex,
+) -> TileTypes {
+    let global_pos = global_tile_position(planet.chunk_dim, chunk_id, tile_id);
+    let tile_count = planet.tile_count();
+    let relative_depth = global_pos.0 as f32 / tile_count.0 as f32;
+
+    // Based on the original chunk dimension, so re-chunking a planet does not change its terrain.
+    let gen_chunk_dim = planet.generation_chunk_dim();
+    let gen_planet_dim = (
+        tile_count.0 / gen_chunk_dim.0.max(1),
+        tile_count.1 / gen_chunk_dim.1.max(1),
+    );
+
+    let surface = surface_row(planet.seed, global_pos.1, gen_chunk_dim.0, tile_count);
+    if global_pos.0 < surface {
+        // Sky
+        return TileTypes::Empty;
+    }
+    if global_pos.0 < surface + topsoil_depth(planet.seed, global_pos.1) {
+        return TileTypes::Dirt;
+    }
+
+    let bedrock_depth = tables.core.bedrock_depth_at(planet.seed, global_pos.1);
+    if global_pos.0 + bedrock_depth >= tile_count.0 {
+        return TileTypes::BedRock;
+    }
+
+    if let Some(tile) = tables.structures.structure_tile(planet.seed, global_pos, tile_count) {
+        return tile;
+    }
+
+    let is_cave = is_cave(planet.seed, global_pos, relative_depth, tile_count.1);
+    if global_pos.0 >= tables.core.core_top_row(gen_planet_dim, gen_chunk_dim) {
+        if is_cave {
+            return tables.core.cave_fill;
+        }
+        let mut rng = tile_rng(planet.seed, planet.chunk_dim, chunk_id, tile_id, SALT_CORE);
+        return tables.core.pick(&mut rng);
+    }
+
+    if is_cave {
+        return TileTypes::Empty;
+    }
+
+    if let Some(ore) = vein_ore(planet.seed, &tables.ores, &tables.biomes, global_pos, tile_count) {
+        return ore;
+    }
+
+    let biome = tables.biomes.blend_at(planet.seed, global_pos.1, tile_count.1);
+    let mut rng = tile_rng(planet.seed, planet.chunk_dim, chunk_id, tile_id, SALT_ORES);
+    tables.ores.pick(relative_depth, &biome, &mut rng)
+}
+
+/// Determines the `TileTypes` of every tile of the given chunk, see [`tile_type`](fn.tile_type.html).
+/// Does not create any entities, so planets can be generated without a running game.
+pub fn chunk_tiles(
+    planet: &Planet,
+    tables: &WorldGenTables,
+    chunk_id: ChunkIndex,
+) -> BTreeMap<TileIndex, TileTypes> {
+    let mut rv = BTreeMap::new();
+    for y in 0 .. planet.chunk_dim.0 {
+        for x in 0 .. planet.chunk_dim.1 {
+            let tile_id = TileIndex(y, x);
+            rv.insert(tile_id, tile_type(planet, tables, chunk_id, tile_id));
+        }
+    }
+    rv
+}
@@ -0,0 +1,87 @@
+use std::f64::consts::PI;
+
+use super::position_hash;
+
+/// Coherent 2D gradient noise (Perlin noise) in the range of roughly `-1.0 ..= 1.0`.
+/// The gradients are derived from the seed and the lattice position,
+/// so any point can be evaluated on its own, without generating its surroundings first.
+/// One lattice cell is one unit of `x` and `y`.
+///
+/// The lattice repeats every `period_x` cells in x-direction,
+/// so the noise continues seamlessly around a planet that is `period_x` cells wide.
+/// A `period_x` of `0` never repeats.
+pub fn perlin(seed: u64, salt: u64, x: f64, y: f64, period_x: u64) -> f64 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix0, ix1) = (wrap_lattice(ix, period_x), wrap_lattice(ix + 1, period_x));
+
+    let dot_00 = lattice_dot(seed, salt, ix0, iy, fx, fy);
+    let dot_10 = lattice_dot(seed, salt, ix1, iy, fx - 1.0, fy);
+    let dot_01 = lattice_dot(seed, salt, ix0, iy + 1, fx, fy - 1.0);
+    let dot_11 = lattice_dot(seed, salt, ix1, iy + 1, fx - 1.0, fy - 1.0);
+
+    let (sx, sy) = (fade(fx), fade(fy));
+    let top = lerp(dot_00, dot_10, sx);
+    let bottom = lerp(dot_01, dot_11, sx);
+
+    // Scales the theoretical maximum of `sqrt(0.5)` up to 1.0.
+    lerp(top, bottom, sy) * 2f64.sqrt()
+}
+
+/// Several octaves of [`perlin`](fn.perlin.html) noise layered on top of each other,
+/// each with double the frequency and half the amplitude of the previous one.
+/// Normalized to roughly `-1.0 ..= 1.0`.
+///
+/// Repeats every `period_x` cells in x-direction, like [`perlin`](fn.perlin.html).
+pub fn fractal(seed: u64, salt: u64, x: f64, y: f64, octaves: u32, period_x: u64) -> f64 {
+    let mut rv = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut period = period_x;
+    let mut amplitude_sum = 0.0;
+
+    for octave in 0 .. octaves.max(1) {
+        rv += amplitude
+            * perlin(seed, salt.wrapping_add(octave as u64), x * frequency, y * frequency, period);
+        amplitude_sum += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+        period = period.saturating_mul(2);
+    }
+
+    rv / amplitude_sum
+}
+
+/// The count of noise cells that fit around a planet `width` tiles wide,
+/// with each cell being roughly `cell_size` tiles wide.
+/// Use `column * cells / width` as x-coordinate to get noise that wraps around the planet.
+pub fn cells_around(width: u64, cell_size: f64) -> u64 {
+    ((width as f64 / cell_size).round() as u64).max(1)
+}
+
+fn wrap_lattice(ix: i64, period_x: u64) -> i64 {
+    if period_x == 0 {
+        ix
+    }
+    else {
+        let period = period_x as i64;
+        ((ix % period) + period) % period
+    }
+}
+
+fn lattice_dot(seed: u64, salt: u64, ix: i64, iy: i64, dx: f64, dy: f64) -> f64 {
+    let hash = position_hash(seed, (iy as u64, ix as u64), salt);
+    // Upper 53 bits to a float in `0.0 .. 1.0`
+    let angle = (hash >> 11) as f64 / (1u64 << 53) as f64 * 2.0 * PI;
+    angle.cos() * dx + angle.sin() * dy
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
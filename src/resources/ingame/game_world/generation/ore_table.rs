@@ -0,0 +1,161 @@
+use rand::Rng;
+
+use crate::entities::tile::TileTypes;
+
+use super::{BiomeBlend, VeinSettings};
+
+/// How often a tile type shows up compared to the others at the same depth.
+/// Scales the weight taken from the weight curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    VeryRare,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 0.5,
+            Rarity::Rare => 0.2,
+            Rarity::VeryRare => 0.05,
+            Rarity::Legendary => 0.01,
+        }
+    }
+}
+
+/// Describes where and how often a single `TileTypes` gets generated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreTableEntry {
+    pub tile: TileTypes,
+    /// The relative depth range (0.0 = surface, 1.0 = bottom of the planet) this tile can appear in.
+    /// Both bounds are inclusive.
+    pub depth: (f32, f32),
+    /// Control points `(relative depth, weight)`, which get linearly interpolated.
+    /// Depths before the first or after the last point use the weight of that point.
+    pub weight_curve: Vec<(f32, f32)>,
+    pub rarity: Rarity,
+    /// If set, this tile also grows in veins spanning multiple tiles.
+    #[serde(default)]
+    pub vein: Option<VeinSettings>,
+}
+
+impl OreTableEntry {
+    /// The weight of this entry at the given relative depth, including its rarity.
+    /// Returns `0.0` outside of the entry's depth range.
+    pub fn weight_at(&self, rel_depth: f32) -> f32 {
+        if rel_depth < self.depth.0 || rel_depth > self.depth.1 {
+            return 0.0;
+        }
+
+        let curve_weight = match (self.weight_curve.first(), self.weight_curve.last()) {
+            (Some(&(first_depth, first_weight)), Some(&(last_depth, last_weight))) => {
+                if rel_depth <= first_depth {
+                    first_weight
+                }
+                else if rel_depth >= last_depth {
+                    last_weight
+                }
+                else {
+                    let mut rv = last_weight;
+                    for points in self.weight_curve.windows(2) {
+                        let (depth_a, weight_a) = points[0];
+                        let (depth_b, weight_b) = points[1];
+                        if rel_depth >= depth_a && rel_depth <= depth_b {
+                            let span = depth_b - depth_a;
+                            rv = if span > 0.0 {
+                                weight_a + (weight_b - weight_a) * (rel_depth - depth_a) / span
+                            }
+                            else {
+                                weight_b
+                            };
+                            break;
+                        }
+                    }
+                    rv
+                }
+            },
+            _ => 0.0,
+        };
+
+        curve_weight.max(0.0) * self.rarity.multiplier()
+    }
+}
+
+/// The distribution of all tile types over the depth of a planet.
+/// Loaded from `Prefabs/world_gen/ore_table.ron`, so it can be rebalanced without recompiling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreTable {
+    /// Used whenever no entry has a weight at the requested depth.
+    pub fallback: TileTypes,
+    pub entries: Vec<OreTableEntry>,
+}
+
+impl Default for OreTable {
+    fn default() -> Self {
+        OreTable {
+            fallback: TileTypes::Rock,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl OreTable {
+    /// Picks a tile type at the given relative depth,
+    /// weighted by the entries' curves and rarities, as well as the biome's multipliers.
+    pub fn pick<R: Rng>(&self, rel_depth: f32, biome: &BiomeBlend<'_>, rng: &mut R) -> TileTypes {
+        let weight = |entry: &OreTableEntry| entry.weight_at(rel_depth) * biome.multiplier(entry.tile);
+        let total_weight: f32 = self.entries.iter().map(|entry| weight(entry)).sum();
+
+        if total_weight <= 0.0 {
+            return self.fallback;
+        }
+
+        let mut roll = rng.gen_range(0.0, total_weight);
+        for entry in self.entries.iter() {
+            let weight = weight(entry);
+            if roll < weight {
+                return entry.tile;
+            }
+            roll -= weight;
+        }
+
+        self.fallback
+    }
+
+    /// Picks the ore of a new vein at the given relative depth,
+    /// weighted by the entries' weights, vein frequencies and the biome's multipliers.
+    /// Returns `None` if no entry grows veins at this depth.
+    pub fn pick_vein<R: Rng>(
+        &self,
+        rel_depth: f32,
+        biome: &BiomeBlend<'_>,
+        rng: &mut R,
+    ) -> Option<(TileTypes, VeinSettings)> {
+        let vein_weight = |entry: &OreTableEntry| match entry.vein {
+            Some(vein) => {
+                entry.weight_at(rel_depth) * biome.multiplier(entry.tile) * vein.frequency.max(0.0)
+            },
+            None => 0.0,
+        };
+        let total_weight: f32 = self.entries.iter().map(|entry| vein_weight(entry)).sum();
+
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0.0, total_weight);
+        for entry in self.entries.iter() {
+            let weight = vein_weight(entry);
+            if roll < weight {
+                return entry.vein.map(|vein| (entry.tile, vein));
+            }
+            roll -= weight;
+        }
+
+        None
+    }
+}
@@ -0,0 +1,69 @@
+use rand::Rng;
+
+use crate::entities::tile::TileTypes;
+
+use super::{position_hash, SALT_BEDROCK};
+
+/// The bottom of a planet: a bedrock layer nobody can drill through,
+/// and above it the core, a zone with its own tiles and hazards.
+/// Loaded from `Prefabs/world_gen/core.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreSettings {
+    /// The count of chunk rows at the bottom of the planet belonging to the core.
+    /// The topmost chunk row never does, no matter how small the planet is.
+    pub chunk_rows: u64,
+    /// Minimum and maximum thickness of the bedrock layer in tiles.
+    /// The bottom row of the planet is always bedrock.
+    pub bedrock_depth: (u64, u64),
+    /// The tile caverns in the core get filled with, instead of being empty.
+    pub cave_fill: TileTypes,
+    /// Tiles of the core and their weights, replacing the ore table and biomes.
+    pub tiles: Vec<(TileTypes, f32)>,
+}
+
+impl Default for CoreSettings {
+    fn default() -> Self {
+        CoreSettings {
+            chunk_rows: 1,
+            bedrock_depth: (1, 1),
+            cave_fill: TileTypes::Empty,
+            tiles: Vec::new(),
+        }
+    }
+}
+
+impl CoreSettings {
+    /// The first tile row belonging to the core, for a planet with the given dimensions.
+    pub fn core_top_row(&self, planet_dim: (u64, u64), chunk_dim: (u64, u64)) -> u64 {
+        let core_chunk_rows = self.chunk_rows.min(planet_dim.0.saturating_sub(1));
+        (planet_dim.0 - core_chunk_rows) * chunk_dim.0
+    }
+
+    /// The thickness of the bedrock layer at the given column in tiles.
+    pub fn bedrock_depth_at(&self, seed: u64, column: u64) -> u64 {
+        let min_depth = self.bedrock_depth.0.max(1);
+        let max_depth = self.bedrock_depth.1.max(min_depth);
+        let hash = position_hash(seed, (0, column), SALT_BEDROCK);
+        min_depth + hash % (max_depth - min_depth + 1)
+    }
+
+    /// Picks a core tile, weighted by the `tiles`.
+    /// Falls back to `Rock`, if no tile has a weight.
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> TileTypes {
+        let total_weight: f32 = self.tiles.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return TileTypes::Rock;
+        }
+
+        let mut roll = rng.gen_range(0.0, total_weight);
+        for &(tile, weight) in self.tiles.iter() {
+            let weight = weight.max(0.0);
+            if roll < weight {
+                return tile;
+            }
+            roll -= weight;
+        }
+
+        TileTypes::Rock
+    }
+}
@@ -0,0 +1,59 @@
+use rand::{prng::XorShiftRng, SeedableRng};
+
+use crate::resources::ingame::game_world::{ChunkIndex, TileIndex};
+
+/// Scrambles the bits of the given value (`SplitMix64` finalizer).
+/// Small changes in the input result in completely different outputs.
+pub fn mix_u64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Combines the world seed with a global tile position (rows, columns) and a `salt`.
+/// The salt allows different generation passes to draw independent numbers for the same tile.
+pub fn position_hash(seed: u64, global_pos: (u64, u64), salt: u64) -> u64 {
+    let mut hash = mix_u64(seed ^ salt.wrapping_mul(0xD6E8_FEB8_6659_FD93));
+    hash = mix_u64(hash ^ global_pos.0);
+    mix_u64(hash ^ global_pos.1.rotate_left(32))
+}
+
+/// Calculates the position of a tile in tiles from the top-left corner of the planet.
+/// Uses (rows, columns).
+pub fn global_tile_position(
+    chunk_dim: (u64, u64),
+    chunk_id: ChunkIndex,
+    tile_id: TileIndex,
+) -> (u64, u64) {
+    (
+        chunk_id.0 * chunk_dim.0 + tile_id.0,
+        chunk_id.1 * chunk_dim.1 + tile_id.1,
+    )
+}
+
+/// Creates a random number generator only depending on the world seed and the position of the tile.
+/// Generating the same tile twice results in the same sequence of numbers.
+pub fn tile_rng(
+    seed: u64,
+    chunk_dim: (u64, u64),
+    chunk_id: ChunkIndex,
+    tile_id: TileIndex,
+    salt: u64,
+) -> XorShiftRng {
+    let global_pos = global_tile_position(chunk_dim, chunk_id, tile_id);
+    hash_rng(position_hash(seed, global_pos, salt))
+}
+
+/// Creates a random number generator from a hash, e.g. one created by [`position_hash`](fn.position_hash.html).
+pub fn hash_rng(hash: u64) -> XorShiftRng {
+    let low = hash;
+    let high = mix_u64(!hash);
+
+    let mut rng_seed = [0u8; 16];
+    for i in 0 .. 8 {
+        rng_seed[i] = (low >> (8 * i)) as u8;
+        rng_seed[i + 8] = (high >> (8 * i)) as u8;
+    }
+    XorShiftRng::from_seed(rng_seed)
+}
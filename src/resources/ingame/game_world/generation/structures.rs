@@ -0,0 +1,176 @@
+use rand::Rng;
+
+use crate::entities::tile::TileTypes;
+
+use super::{hash_rng, position_hash, Rarity, SALT_STRUCTURES};
+
+/// A small, pre-authored arrangement of tiles, like an abandoned mine shaft or a treasure vault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructureTemplate {
+    pub name: String,
+    /// The relative depth range (0.0 = surface, 1.0 = bottom of the planet) the top-left corner can be placed in.
+    pub depth: (f32, f32),
+    pub rarity: Rarity,
+    /// Relative weight when choosing a structure, multiplied by the rarity.
+    pub frequency: f32,
+    /// Maps the characters used in `rows` to tile types.
+    /// Characters missing here, like spaces, keep whatever tile would have been generated otherwise.
+    pub palette: Vec<(char, TileTypes)>,
+    /// The tiles of the structure, from top to bottom, one character per tile.
+    pub rows: Vec<String>,
+}
+
+impl StructureTemplate {
+    /// The size of the structure in tiles (rows, columns).
+    pub fn size(&self) -> (u64, u64) {
+        let width = self.rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        (self.rows.len() as u64, width as u64)
+    }
+
+    /// The tile at the given offset (rows, columns) from the top-left corner of the structure,
+    /// or `None` if the structure leaves this tile alone.
+    pub fn tile_at(&self, offset: (u64, u64)) -> Option<TileTypes> {
+        let symbol = self.rows.get(offset.0 as usize)?.chars().nth(offset.1 as usize)?;
+        self.palette
+            .iter()
+            .find(|(palette_symbol, _)| *palette_symbol == symbol)
+            .map(|(_, tile)| *tile)
+    }
+
+    fn weight_at(&self, rel_depth: f32) -> f32 {
+        if rel_depth < self.depth.0 || rel_depth > self.depth.1 {
+            0.0
+        }
+        else {
+            self.frequency.max(0.0) * self.rarity.multiplier()
+        }
+    }
+}
+
+/// All structures that might be placed on a planet.
+/// Loaded from `Prefabs/world_gen/structures.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructureTable {
+    /// Size of a structure region in tiles. Each region holds at most one structure,
+    /// which may reach into the neighbouring regions to the right and bottom.
+    /// Structures larger than a region get cut off.
+    pub region_size: u64,
+    /// The chance of a region holding a structure, `0.0 ..= 1.0`.
+    pub region_chance: f32,
+    pub structures: Vec<StructureTemplate>,
+}
+
+impl Default for StructureTable {
+    fn default() -> Self {
+        StructureTable {
+            region_size: 32,
+            region_chance: 0.0,
+            structures: Vec::new(),
+        }
+    }
+}
+
+/// A structure placed on the planet, fully determined by the seed and the region it originates in.
+#[derive(Debug, Clone, Copy)]
+struct PlacedStructure<'a> {
+    template: &'a StructureTemplate,
+    /// The top-left corner in tiles (rows, columns).
+    origin:   (u64, u64),
+}
+
+impl StructureTable {
+    /// Checks whether the tile at the given global position (rows, columns) is part of a structure,
+    /// and returns the structure's tile if it is.
+    ///
+    /// Structures are placed per region, and a tile checks its own region as well as the ones to its left and top.
+    /// So a structure crossing a chunk border continues in the neighbouring chunk, no matter which chunk gets generated first.
+    /// Regions wrap around the planet's width, `tile_count` being the size of the planet in tiles (rows, columns).
+    pub fn structure_tile(
+        &self,
+        seed: u64,
+        global_pos: (u64, u64),
+        tile_count: (u64, u64),
+    ) -> Option<TileTypes> {
+        if self.structures.is_empty() || self.region_chance <= 0.0 {
+            return None;
+        }
+
+        let region_size = self.region_size.max(1);
+        let region = (global_pos.0 / region_size, global_pos.1 / region_size);
+        let region_count_x = ((tile_count.1 + region_size - 1) / region_size).max(1);
+        let width = tile_count.1.max(1);
+
+        let mut region_cols = vec![region.1];
+        let left_col = (region.1 + region_count_x - 1) % region_count_x;
+        if left_col != region.1 {
+            region_cols.push(left_col);
+        }
+
+        for region_row in region.0.saturating_sub(1) ..= region.0 {
+            for &region_col in region_cols.iter() {
+                if let Some(placed) = self.region_structure(seed, (region_row, region_col), tile_count) {
+                    if global_pos.0 < placed.origin.0 {
+                        continue;
+                    }
+                    let offset = (
+                        global_pos.0 - placed.origin.0,
+                        (global_pos.1 + width - placed.origin.1) % width,
+                    );
+                    if offset.0 >= region_size || offset.1 >= region_size {
+                        continue;
+                    }
+                    if let Some(tile) = placed.template.tile_at(offset) {
+                        return Some(tile);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn region_structure(
+        &self,
+        seed: u64,
+        region: (u64, u64),
+        tile_count: (u64, u64),
+    ) -> Option<PlacedStructure<'_>> {
+        let region_size = self.region_size.max(1);
+        let mut rng = hash_rng(position_hash(seed, region, SALT_STRUCTURES));
+        if rng.gen_range(0.0, 1.0) >= self.region_chance {
+            return None;
+        }
+
+        let origin = (
+            region.0 * region_size + rng.gen_range(0, region_size),
+            region.1 * region_size + rng.gen_range(0, region_size),
+        );
+        if origin.0 >= tile_count.0 || origin.1 >= tile_count.1 {
+            return None;
+        }
+        let rel_depth = origin.0 as f32 / tile_count.0 as f32;
+
+        let total_weight: f32 = self
+            .structures
+            .iter()
+            .map(|template| template.weight_at(rel_depth))
+            .sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0.0, total_weight);
+        for template in self.structures.iter() {
+            let weight = template.weight_at(rel_depth);
+            if roll < weight {
+                return Some(PlacedStructure {
+                    template,
+                    origin,
+                });
+            }
+            roll -= weight;
+        }
+
+        None
+    }
+}
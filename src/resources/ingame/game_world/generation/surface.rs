@@ -0,0 +1,36 @@
+use super::{noise, position_hash, SALT_SURFACE, SALT_TOPSOIL};
+
+/// Width of a hill in tiles.
+const HILL_WIDTH: f64 = 36.0;
+/// Maximum height of hills and depth of valleys, relative to the height of a chunk.
+const HILL_HEIGHT: f64 = 0.6;
+/// Rows at the top of the planet that always stay air, no matter how high a hill grows.
+const MIN_SKY_ROWS: u64 = 2;
+/// Minimum and maximum thickness of the dirt layer on top of the surface in tiles.
+const TOPSOIL_DEPTH: (u64, u64) = (1, 3);
+
+/// The row of the topmost solid tile at the given column, based on a 1D heightmap across the planet's width.
+/// `tile_count` is the size of the planet in tiles (rows, columns), the heightmap wraps around its width.
+///
+/// The average surface sits at the top of the second chunk row, hills rise into the first chunk row.
+pub fn surface_row(seed: u64, column: u64, chunk_height: u64, tile_count: (u64, u64)) -> u64 {
+    let tile_count_y = tile_count.0;
+    let hill_count = noise::cells_around(tile_count.1, HILL_WIDTH);
+    let x = column as f64 * hill_count as f64 / tile_count.1.max(1) as f64;
+    let hills = noise::fractal(seed, SALT_SURFACE, x, 0.5, 3, hill_count);
+    let sea_level = chunk_height as f64;
+    let row = (sea_level - hills * HILL_HEIGHT * chunk_height as f64).round();
+
+    if row < MIN_SKY_ROWS as f64 {
+        MIN_SKY_ROWS.min(tile_count_y.saturating_sub(1))
+    }
+    else {
+        (row as u64).min(tile_count_y.saturating_sub(1))
+    }
+}
+
+/// The thickness of the dirt layer at the given column in tiles.
+pub fn topsoil_depth(seed: u64, column: u64) -> u64 {
+    let hash = position_hash(seed, (0, column), SALT_TOPSOIL);
+    TOPSOIL_DEPTH.0 + hash % (TOPSOIL_DEPTH.1 - TOPSOIL_DEPTH.0 + 1)
+}
@@ -0,0 +1,169 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::entities::tile::TileTypes;
+
+use super::{hash_rng, position_hash, BiomeTable, OreTable, SALT_VEINS, SALT_VEIN_EDGES};
+
+/// Size of a vein region in tiles.
+/// Each region seeds its own veins, which may grow into the directly neighbouring regions.
+const VEIN_REGION_SIZE: u64 = 16;
+/// Maximum count of veins originating in a single region.
+const VEINS_PER_REGION: u64 = 2;
+/// How much of a vein's edge gets randomly frayed, relative to its size.
+const VEIN_EDGE_JITTER: f64 = 0.35;
+
+/// The shape a vein of ore grows in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VeinShape {
+    /// A roundish cluster.
+    Blob,
+    /// A long, thin streak with a random direction.
+    Streak,
+}
+
+/// Describes the veins of a single ore, part of an [`OreTableEntry`](struct.OreTableEntry.html).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VeinSettings {
+    pub shape: VeinShape,
+    /// Minimum and maximum extent in tiles, meaning the diameter of a blob or the length of a streak.
+    /// Gets capped to the size of a vein region.
+    pub size: (u64, u64),
+    /// Relative weight when choosing the ore of a new vein,
+    /// multiplied by the entry's weight at the depth of the vein's origin.
+    pub frequency: f32,
+}
+
+/// A single vein, fully determined by the seed and the region it originates in.
+#[derive(Debug, Clone, Copy)]
+struct Vein {
+    ore:       TileTypes,
+    shape:     VeinShape,
+    /// Center of a blob, or starting point of a streak (rows, columns).
+    origin:    (f64, f64),
+    /// Direction of a streak as (rows, columns), normalized.
+    direction: (f64, f64),
+    /// Diameter of a blob, or length of a streak.
+    size:      f64,
+    /// Thickness of a streak.
+    thickness: f64,
+}
+
+impl Vein {
+    /// `width` is the width of the planet in tiles, the shorter way around the planet is used for the distance.
+    fn contains(&self, pos: (f64, f64), jitter: f64, width: f64) -> bool {
+        let d_row = pos.0 - self.origin.0;
+        let d_col = {
+            let d_col = pos.1 - self.origin.1;
+            d_col - width * (d_col / width).round()
+        };
+        match self.shape {
+            VeinShape::Blob => {
+                let radius = 0.5 * self.size;
+                let distance = (d_row * d_row + d_col * d_col).sqrt();
+                distance <= radius * (1.0 - VEIN_EDGE_JITTER * jitter)
+            },
+            VeinShape::Streak => {
+                // Projection onto the streak, clamped to its length.
+                let along = (d_row * self.direction.0 + d_col * self.direction.1)
+                    .max(0.0)
+                    .min(self.size);
+                let (off_row, off_col) = (
+                    d_row - along * self.direction.0,
+                    d_col - along * self.direction.1,
+                );
+                let distance = (off_row * off_row + off_col * off_col).sqrt();
+                distance <= 0.5 * self.thickness * (1.0 - VEIN_EDGE_JITTER * jitter)
+            },
+        }
+    }
+}
+
+/// Checks whether the tile at the given global position (rows, columns) is part of an ore vein,
+/// and returns the ore of the vein if it is.
+///
+/// Veins are seeded per region, and a tile checks its own region as well as all neighbouring ones.
+/// So a vein crossing a chunk border continues in the neighbouring chunk, no matter which chunk gets generated first.
+/// Regions wrap around the planet's width, `tile_count` being the size of the planet in tiles (rows, columns).
+/// The ore of a vein depends on the biome at its origin.
+pub fn vein_ore(
+    seed: u64,
+    ores: &OreTable,
+    biomes: &BiomeTable,
+    global_pos: (u64, u64),
+    tile_count: (u64, u64),
+) -> Option<TileTypes> {
+    let region = (
+        global_pos.0 / VEIN_REGION_SIZE,
+        global_pos.1 / VEIN_REGION_SIZE,
+    );
+    let region_count_x = ((tile_count.1 + VEIN_REGION_SIZE - 1) / VEIN_REGION_SIZE).max(1);
+    let pos = (global_pos.0 as f64 + 0.5, global_pos.1 as f64 + 0.5);
+    let jitter = (position_hash(seed, global_pos, SALT_VEIN_EDGES) >> 11) as f64
+        / (1u64 << 53) as f64;
+
+    let mut region_cols = Vec::with_capacity(3);
+    for offset in 0 .. 3 {
+        let region_col = (region.1 + region_count_x + offset - 1) % region_count_x;
+        if !region_cols.contains(&region_col) {
+            region_cols.push(region_col);
+        }
+    }
+
+    for region_row in region.0.saturating_sub(1) ..= region.0 + 1 {
+        for &region_col in region_cols.iter() {
+            for vein in region_veins(seed, ores, biomes, (region_row, region_col), tile_count) {
+                if vein.contains(pos, jitter, tile_count.1.max(1) as f64) {
+                    return Some(vein.ore);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn region_veins(
+    seed: u64,
+    ores: &OreTable,
+    biomes: &BiomeTable,
+    region: (u64, u64),
+    tile_count: (u64, u64),
+) -> Vec<Vein> {
+    let tile_count_y = tile_count.0;
+    let mut rng = hash_rng(position_hash(seed, region, SALT_VEINS));
+    let vein_count = rng.gen_range(0, VEINS_PER_REGION + 1);
+    let mut rv = Vec::with_capacity(vein_count as usize);
+
+    for _ in 0 .. vein_count {
+        let origin = (
+            (region.0 * VEIN_REGION_SIZE) as f64 + rng.gen_range(0.0, VEIN_REGION_SIZE as f64),
+            (region.1 * VEIN_REGION_SIZE) as f64 + rng.gen_range(0.0, VEIN_REGION_SIZE as f64),
+        );
+        if origin.0 >= tile_count_y as f64 || origin.1 >= tile_count.1 as f64 {
+            continue;
+        }
+        let rel_depth = (origin.0 / tile_count_y as f64) as f32;
+        let biome = biomes.blend_at(seed, origin.1 as u64, tile_count.1);
+
+        if let Some((ore, settings)) = ores.pick_vein(rel_depth, &biome, &mut rng) {
+            let max_size = settings.size.1.min(VEIN_REGION_SIZE).max(1);
+            let min_size = settings.size.0.min(max_size);
+            let size = rng.gen_range(min_size as f64, max_size as f64 + 1.0);
+            let angle = rng.gen_range(0.0, PI);
+            let thickness = rng.gen_range(1.0, 2.5);
+
+            rv.push(Vein {
+                ore,
+                shape: settings.shape,
+                origin,
+                direction: (angle.sin(), angle.cos()),
+                size,
+                thickness,
+            });
+        }
+    }
+
+    rv
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use amethyst::core::{nalgebra::Vector3, transform::components::Transform};
+
+use crate::resources::RenderConfig;
+
+use super::{ChunkError, ChunkIndex, GameWorldError, Planet, TileError, TileIndex};
+
+/// The index of a tile on the whole [Planet](struct.Planet.html), counted in tiles from its top-left corner.
+/// Converts between all the coordinates used for the game world:
+/// - `(ChunkIndex, TileIndex)`, used to store tiles in chunks,
+/// - world-space translations, where each tile's sprite is centered on the tile's translation,
+/// - pixel coordinates `(x, y)`, where each tile covers `tile_size` pixels starting at the planet's top-left corner.
+///
+/// The planet wraps around in x-direction, so every conversion into a `GlobalTileIndex` wraps the column,
+/// while rows above or below the planet result in an `IndexOutOfBounds` error.
+/// Conversions out of a `GlobalTileIndex` always refer to the first lap around the planet.
+///
+/// Uses (rows, columns).
+#[derive(PartialEq, Eq, Copy, Clone, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct GlobalTileIndex(pub u64, pub u64);
+
+impl GlobalTileIndex {
+    /// Creates a global tile index from a row and column, which may lie outside of the planet.
+    /// The column gets wrapped around the planet.
+    pub fn new(planet: &Planet, row: i64, column: i64) -> Result<Self, GameWorldError> {
+        let tile_count = planet.tile_count();
+        if row < 0 || row as u64 >= tile_count.0 || tile_count.1 == 0 {
+            return Err(GameWorldError::ChunkProblem(ChunkError::IndexOutOfBounds));
+        }
+
+        let width = tile_count.1 as i64;
+        Ok(GlobalTileIndex(row as u64, (((column % width) + width) % width) as u64))
+    }
+
+    /// The global index of a tile in a chunk.
+    /// The chunk index gets [clamped](struct.Planet.html#method.clamp_chunk_index),
+    /// tile indexes exceeding the chunk-dim result in an error.
+    pub fn from_indices(
+        planet: &Planet,
+        chunk_id: ChunkIndex,
+        tile_id: TileIndex,
+    ) -> Result<Self, GameWorldError> {
+        let chunk_id = Planet::clamp_chunk_index(planet, chunk_id)?;
+        if tile_id.0 >= planet.chunk_dim.0 || tile_id.1 >= planet.chunk_dim.1 {
+            return Err(GameWorldError::TileProblem(TileError::IndexOutOfBounds));
+        }
+
+        Ok(GlobalTileIndex(
+            chunk_id.0 * planet.chunk_dim.0 + tile_id.0,
+            chunk_id.1 * planet.chunk_dim.1 + tile_id.1,
+        ))
+    }
+
+    /// The chunk this tile belongs to, and its index within that chunk.
+    pub fn to_indices(self, planet: &Planet) -> (ChunkIndex, TileIndex) {
+        (self.chunk_index(planet), self.tile_index(planet))
+    }
+
+    /// The chunk this tile belongs to.
+    pub fn chunk_index(self, planet: &Planet) -> ChunkIndex {
+        ChunkIndex(
+            self.0 / planet.chunk_dim.0.max(1),
+            self.1 / planet.chunk_dim.1.max(1),
+        )
+    }
+
+    /// The index of this tile within its chunk.
+    pub fn tile_index(self, planet: &Planet) -> TileIndex {
+        TileIndex(
+            self.0 % planet.chunk_dim.0.max(1),
+            self.1 % planet.chunk_dim.1.max(1),
+        )
+    }
+
+    /// The tile whose sprite covers the given world-space translation.
+    pub fn from_translation(
+        translation: &Vector3<f32>,
+        render_config: &RenderConfig,
+        planet: &Planet,
+    ) -> Result<Self, GameWorldError> {
+        let (tile_height, tile_width) = render_config.tile_size;
+        // Sprites are centered on the translation of their tile.
+        let row = (translation.y / tile_height + 0.5).floor();
+        let column = (translation.x / tile_width + 0.5).floor();
+
+        Self::new(planet, row as i64, column as i64)
+    }
+
+    /// The tile whose sprite covers the translation of the given transform.
+    pub fn from_transform(
+        transform: &Transform,
+        render_config: &RenderConfig,
+        planet: &Planet,
+    ) -> Result<Self, GameWorldError> {
+        Self::from_translation(transform.translation(), render_config, planet)
+    }
+
+    /// The world-space translation of this tile, which is the center of its sprite.
+    pub fn to_translation(self, render_config: &RenderConfig) -> Vector3<f32> {
+        let (tile_height, tile_width) = render_config.tile_size;
+        Vector3::new(self.1 as f32 * tile_width, self.0 as f32 * tile_height, 0.0)
+    }
+
+    /// The tile covering the given pixel `(x, y)`, counted from the planet's top-left corner.
+    pub fn from_pixel(
+        pixel: (u64, u64),
+        render_config: &RenderConfig,
+        planet: &Planet,
+    ) -> Result<Self, GameWorldError> {
+        let (tile_height, tile_width) = render_config.tile_size;
+        let row = (pixel.1 as f32 / tile_height).floor();
+        let column = (pixel.0 as f32 / tile_width).floor();
+
+        Self::new(planet, row as i64, column as i64)
+    }
+
+    /// The top-left pixel `(x, y)` of this tile, counted from the planet's top-left corner.
+    pub fn to_pixel(self, render_config: &RenderConfig) -> (u64, u64) {
+        let (tile_height, tile_width) = render_config.tile_size;
+        (
+            (self.1 as f32 * tile_width) as u64,
+            (self.0 as f32 * tile_height) as u64,
+        )
+    }
+
+    /// The tile at the given offset (rows, columns) from this one, wrapping around the planet.
+    pub fn offset(self, planet: &Planet, rows: i64, columns: i64) -> Result<Self, GameWorldError> {
+        Self::new(planet, self.0 as i64 + rows, self.1 as i64 + columns)
+    }
+}
+
+impl fmt::Display for GlobalTileIndex {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("GlobalTileIndex(")?;
+        fmt.write_str(&self.0.to_string())?;
+        fmt.write_str(", ")?;
+        fmt.write_str(&self.1.to_string())?;
+        fmt.write_str(")")?;
+        Ok(())
+    }
+}
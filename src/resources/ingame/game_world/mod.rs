@@ -1,9 +1,15 @@
 mod chunk;
+mod global_tile;
 mod planet;
 mod tile;
 
+pub mod chunk_format;
+pub mod generation;
+pub mod region;
+
 pub use self::{
     chunk::*,
+    global_tile::*,
     tile::*,
     planet::*,
 };
@@ -22,6 +28,16 @@ pub enum ChunkError {
     NotImplemented,
     IndexOutOfBounds,
     NotFound,
+    /// The chunk is part of the planet, but currently not loaded.
+    NotLoaded(ChunkIndex),
+    /// Chunks are still loaded, although the operation requires all of them to be unloaded.
+    StillLoaded,
+    /// The chunk dimension does not fit the planet.
+    InvalidDimension,
+    /// Reading or writing a chunk file failed, details get logged.
+    Io,
+    /// A chunk file could not be decoded or encoded, details get logged.
+    Corrupt,
 }
 
 use crate::entities::EntitySpriteRender;
@@ -31,6 +47,12 @@ pub enum TileError {
     #[allow(dead_code)]
     NotImplemented,
     IndexOutOfBounds,
+    /// The chunk is loaded, but does not contain the tile.
+    NotFound,
+    /// The chunk already contains a tile at this index, replace it instead.
+    AlreadyExists,
+    /// The tile is [indestructible](../../../entities/tile/enum.TileTypes.html#method.is_indestructible), e.g. bedrock.
+    Indestructible,
     SpriteRenderNotFound(EntitySpriteRender),
 }
 
@@ -49,6 +71,8 @@ use crate::{
     resources::{GameSprites, RenderConfig},
 };
 
+use self::generation::WorldGenTables;
+
 /// Internal use only (for the Chunk-Hotloading), do not use!
 pub struct TileGenerationStorages<'a> {
     pub entities: Read<'a, EntitiesRes>,
@@ -59,4 +83,5 @@ pub struct TileGenerationStorages<'a> {
     pub game_sprites: Read<'a, GameSprites, DefaultProvider>,
     pub render_config: Read<'a, RenderConfig, DefaultProvider>,
     pub flipped_vertical: Storage<'a, Flipped, FetchMut<'a, MaskedStorage<Flipped>>>, 
+    pub world_gen_tables: Read<'a, WorldGenTables, DefaultProvider>,
 }
\ No newline at end of file
@@ -1,25 +1,34 @@
 use std::{
-    collections::{hash_map, BTreeMap, HashMap},
-    fs,
+    collections::{hash_map, BTreeMap, BTreeSet, HashMap},
+    fs, io,
     path::*,
+    sync::Arc,
 };
 
 use ron;
-use serde::Serializer;
 
 use amethyst::{
     core::{nalgebra::Vector3, transform::components::Transform},
     ecs::{prelude::*, world::EntitiesRes,},
     shred::DefaultProvider,
+    shrev::EventChannel,
 };
 
 use crate::{
     entities::tile::TileTypes,
-    resources::RenderConfig,
+    events::planet_events::TileEvent,
+    resources::{
+        ingame::{SaveTransaction, SavegamePaths},
+        RenderConfig,
+    },
 };
 
 use super::{
-    ChunkIndex, Chunk, GameWorldError, TileIndex, ChunkError, TileGenerationStorages, 
+    chunk_format,
+    generation::{self, WorldGenTables},
+    region::{RegionError, RegionFile, RegionIndex},
+    ChunkIndex, Chunk, GameWorldError, GlobalTileIndex, TileIndex, ChunkError, TileError,
+    TileGenerationStorages,
 };
 
 /// This is a resource.
@@ -30,17 +39,52 @@ pub struct Planet {
     /// The dimension of a planet expressed in the count of chunks in x and y direction.
     /// Differs based on the setting `Planet size` when creating a new game.
     pub planet_dim: (u64, u64),
-    /// TODO: Make adjustable while playing. Requires reassigning tiles to new chunks.
     /// The dimension of a chunk expressed in tilecount in x and y direction.
-    /// Cannot be changed once the game was created (at least for now).
+    /// Can be changed for existing planets by [re-chunking](struct.Planet.html#method.rechunk) them.
     pub chunk_dim: (u64, u64),
+    /// The world seed every tile of this planet is derived from.
+    /// Chunks that were never saved look exactly the same when they get generated again.
+    /// Savegames from before seeds existed default to `0`.
+    #[serde(default)]
+    pub seed: u64,
+    /// If set, chunks that were generated but never modified do not get written to disk,
+    /// since generating them again results in the same tiles.
+    /// Saves disk space, but those chunks change whenever the world generation tables get rebalanced.
+    #[serde(default)]
+    pub skip_unmodified_chunks: bool,
+    // The chunk dimension the planet was generated with, set when it gets re-chunked.
+    // Some generation passes are based on chunk rows, so this keeps chunks that were never saved unchanged.
+    #[serde(default)]
+    generation_chunk_dim: Option<(u64, u64)>,
     // A map of individual chunks of the planet, only a small number is loaded at a time.
     // Chunks that are too far from the player get serialized and stored to the disk.
     // Private to prevent users from meddling with it.
     #[serde(skip_serializing, default = "serde_de_empty_hash_map")]
     chunks: HashMap<ChunkIndex, Chunk>,
+    // The tiles of chunks that changed and got unloaded since the session was saved the last time.
+    // Kept until the next save, so the chunks get committed together with the session and the players,
+    // unless there are too many of them, see `limit_unsaved_chunks`.
+    // Shared, so copies of the planet for autosaving do not copy the tiles.
+    #[serde(skip_serializing, skip_deserializing)]
+    unsaved_chunks: HashMap<ChunkIndex, Arc<BTreeMap<TileIndex, TileTypes>>>,
+    // The x-translation newly created chunks get placed closest to, usually the player's.
+    // Since the planet wraps around in x-direction, every chunk could be rendered left or right of the player.
+    #[serde(skip_serializing, skip_deserializing)]
+    render_focus: f32,
 }
 
+/// A tile of a loaded chunk, found by [`Planet::get_tile_at`](struct.Planet.html#method.get_tile_at).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileLookup {
+    pub chunk: ChunkIndex,
+    pub tile: TileIndex,
+    pub tile_type: TileTypes,
+    pub entity: Entity,
+}
+
+// The count of unloaded chunks kept in memory until they get written on their own, see `Planet::limit_unsaved_chunks`.
+const UNSAVED_CHUNK_LIMIT: usize = 64;
+
 pub fn serde_de_empty_hash_map() -> HashMap<ChunkIndex, Chunk> {HashMap::with_capacity(9)}
 
 // public interface
@@ -48,6 +92,7 @@ impl Planet {
     pub fn new(
         planet_dim: (u64, u64),
         chunk_dim: (u64, u64),
+        seed: u64,
         render_config: &RenderConfig,
     ) -> Planet {
         // Chunk of the player + render distance in two directions (left+right | top+bottom)
@@ -56,8 +101,136 @@ impl Planet {
         Planet {
             planet_dim,
             chunk_dim,
+            seed,
+            skip_unmodified_chunks: false,
+            generation_chunk_dim: None,
             chunks: HashMap::with_capacity(chunk_count as usize),
+            unsaved_chunks: HashMap::new(),
+            render_focus: 0.0,
+        }
+    }
+
+    /// The dimension of the planet expressed in tilecount (rows, columns).
+    pub fn tile_count(&self) -> (u64, u64) {
+        (
+            self.planet_dim.0 * self.chunk_dim.0,
+            self.planet_dim.1 * self.chunk_dim.1,
+        )
+    }
+
+    /// The next chunk dimension the planet can be [re-chunked](struct.Planet.html#method.rechunk) to,
+    /// with larger or smaller chunks along both axes.
+    /// Axes already at their limit keep their size, `None` if both are.
+    pub fn next_chunk_dim(&self, b_larger: bool) -> Option<(u64, u64)> {
+        let tile_count = self.tile_count();
+        let next = |count: u64, current: u64| -> u64 {
+            let divisor = if b_larger {
+                (current + 1..=count).find(|size| count % size == 0)
+            } else {
+                (1..current).rev().find(|size| count % size == 0)
+            };
+            divisor.unwrap_or(current)
+        };
+
+        let next_dim = (next(tile_count.0, self.chunk_dim.0), next(tile_count.1, self.chunk_dim.1));
+        if next_dim == self.chunk_dim {
+            None
+        } else {
+            Some(next_dim)
+        }
+    }
+
+    /// The chunk dimension the world generation is based on.
+    /// Equals the `chunk_dim`, unless the planet was [re-chunked](struct.Planet.html#method.rechunk).
+    pub fn generation_chunk_dim(&self) -> (u64, u64) {
+        self.generation_chunk_dim.unwrap_or(self.chunk_dim)
+    }
+
+    /// A copy of this planet without any chunks loaded, but with the tiles of unloaded chunks that were not saved yet,
+    /// e.g. to [write chunk tiles](struct.Planet.html#method.write_chunk_tiles) on another thread.
+    /// The tiles are shared with this planet instead of being copied.
+    pub fn without_chunks(&self) -> Planet {
+        Planet {
+            planet_dim: self.planet_dim,
+            chunk_dim: self.chunk_dim,
+            seed: self.seed,
+            skip_unmodified_chunks: self.skip_unmodified_chunks,
+            generation_chunk_dim: self.generation_chunk_dim,
+            chunks: HashMap::new(),
+            unsaved_chunks: self.unsaved_chunks.clone(),
+            render_focus: self.render_focus,
+        }
+    }
+
+    /// Whether the given tile lies above the surface generated at its column, i.e. in the open sky.
+    /// Tiles dug out below the surface do not count.
+    pub fn is_above_surface(&self, tile: GlobalTileIndex) -> bool {
+        tile.0 < generation::surface_row(self.seed, tile.1, self.generation_chunk_dim().0, self.tile_count())
+    }
+
+    /// The width of the planet in world units, after which it wraps around.
+    pub fn width(&self, render_config: &RenderConfig) -> f32 {
+        self.tile_count().1 as f32 * render_config.tile_size.1
+    }
+
+    /// Wraps a chunk column, which may be negative or exceed the planet-dim, around the planet.
+    pub fn wrap_chunk_column(&self, column: i64) -> u64 {
+        let width = self.planet_dim.1.max(1) as i64;
+        (((column % width) + width) % width) as u64
+    }
+
+    /// All chunks within the given distance of a chunk, which should be loaded while a player is on it.
+    /// Rows are clamped to the planet, columns wrap around it.
+    pub fn chunks_around(&self, chunk_id: ChunkIndex, distance: u64) -> Vec<ChunkIndex> {
+        if self.planet_dim.0 == 0 {
+            return Vec::new();
+        }
+        let lower_y = chunk_id.0.saturating_sub(distance);
+        let upper_y = chunk_id.0.saturating_add(distance).min(self.planet_dim.0 - 1);
+        let lower_x = chunk_id.1 as i64 - distance as i64;
+        let upper_x = chunk_id.1 as i64 + distance as i64;
+
+        let mut rv = Vec::new();
+        for y in lower_y ..= upper_y {
+            for x in lower_x ..= upper_x {
+                rv.push(ChunkIndex(y, self.wrap_chunk_column(x)));
+            }
+        }
+        // On planets narrower than the distance, columns wrap around onto each other.
+        rv.sort();
+        rv.dedup();
+        rv
+    }
+
+    /// Sets the x-translation new chunks get placed closest to,
+    /// see [`chunk_base_transform`](struct.Planet.html#method.chunk_base_transform).
+    pub fn set_render_focus(&mut self, x_transl: f32) {
+        self.render_focus = x_transl;
+    }
+
+    /// The transform of the top-left tile of the given chunk.
+    /// Chunks repeat every [planet width](struct.Planet.html#method.width) in x-direction,
+    /// the repetition closest to the [render focus](struct.Planet.html#method.set_render_focus) gets chosen,
+    /// so chunks on the other side of the planet's seam show up next to the player.
+    pub fn chunk_base_transform(&self, chunk_id: ChunkIndex, render_config: &RenderConfig) -> Transform {
+        let chunk_width = self.chunk_dim.1 as f32 * render_config.tile_size.1;
+        let width = self.width(render_config);
+
+        let mut translation = match GlobalTileIndex::from_indices(self, chunk_id, TileIndex(0, 0)) {
+            Ok(global_tile) => global_tile.to_translation(render_config),
+            Err(e) => {
+                error!("| Base transform of {:?} requested: {:?}.", chunk_id, e);
+                Vector3::new(0.0, 0.0, 0.0)
+            }
+        };
+        if width > 0.0 {
+            let chunk_center = translation.x + 0.5 * chunk_width;
+            translation.x += width * ((self.render_focus - chunk_center) / width).round();
         }
+
+        let mut transform = Transform::default();
+        transform.set_position(translation);
+        transform
     }
 
     /// Tries to fetch a chunk from the HashMap.
@@ -77,16 +250,84 @@ impl Planet {
         }
     }
 
-    /// Tile indexes out of tile-dim bounds return `None`.
-    #[allow(dead_code)]
-    pub fn get_tiletype(_chunk: ChunkIndex, _tile: TileIndex) -> Option<TileTypes> {
-        // TODO:
-        error!("Not implemented yet.");
-        None
+    /// Looks up the `TileTypes` of a tile in a loaded chunk.
+    /// The chunk index gets [clamped](struct.Planet.html#method.clamp_chunk_index),
+    /// tile indexes out of tile-dim bounds and tiles of unloaded chunks return `None`.
+    pub fn get_tiletype(&self, chunk: ChunkIndex, tile: TileIndex) -> Option<TileTypes> {
+        match self.get_chunk(chunk) {
+            Ok(Some(chunk)) => chunk.get_tile_type(tile),
+            _ => None,
+        }
+    }
+
+    /// Looks up the given tile across all loaded chunks.
+    ///
+    /// Returns `GameWorldError::ChunkProblem(ChunkError::NotLoaded(chunk_id))` if the tile's chunk is currently not loaded.
+    pub fn get_tile(&self, global_tile: GlobalTileIndex) -> Result<TileLookup, GameWorldError> {
+        let (chunk_id, tile_id) = global_tile.to_indices(self);
+
+        let chunk = match self.chunks.get(&chunk_id) {
+            Some(chunk) => chunk,
+            None => return Err(GameWorldError::ChunkProblem(ChunkError::NotLoaded(chunk_id))),
+        };
+
+        match (chunk.get_tile_type(tile_id), chunk.get_tile_entity(tile_id)) {
+            (Some(tile_type), Some(entity)) => Ok(TileLookup {
+                chunk: chunk_id,
+                tile: tile_id,
+                tile_type,
+                entity,
+            }),
+            _ => Err(GameWorldError::TileProblem(TileError::NotFound)),
+        }
+    }
+
+    /// Looks up the tile at the given world position across all loaded chunks.
+    /// The planet wraps around in x-direction, so any x-translation is valid.
+    ///
+    /// Returns `GameWorldError::ChunkProblem(ChunkError::NotLoaded(chunk_id))` if the position lies on the planet,
+    /// but its chunk is currently not loaded, and `IndexOutOfBounds` if it lies above or below the planet.
+    pub fn get_tile_at(
+        &self,
+        transform: &Transform,
+        render_config: &RenderConfig,
+    ) -> Result<TileLookup, GameWorldError> {
+        self.get_tile(GlobalTileIndex::from_transform(transform, render_config, self)?)
+    }
+
+    /// Looks up the tile at the given world coordinates, see [`get_tile_at`](struct.Planet.html#method.get_tile_at).
+    pub fn get_tile_at_position(
+        &self,
+        x: f32,
+        y: f32,
+        render_config: &RenderConfig,
+    ) -> Result<TileLookup, GameWorldError> {
+        let translation = Vector3::new(x, y, 0.0);
+        self.get_tile(GlobalTileIndex::from_translation(&translation, render_config, self)?)
+    }
+
+    /// Finds the chunk and tile index of a tile entity, e.g. one returned by a collision.
+    /// Returns `None` if the entity is not a tile of any loaded chunk.
+    pub fn find_tile(&self, entity: Entity) -> Option<(ChunkIndex, TileIndex)> {
+        self.chunks
+            .iter()
+            .filter_map(|(&chunk_id, chunk)| chunk.get_tile_index(entity).map(|tile_id| (chunk_id, tile_id)))
+            .next()
+    }
+
+    /// The world position of the tile right above the generated surface at the given column,
+    /// expressed in tiles from the left edge of the planet.
+    /// Use it to place things on top of the terrain, like the player's ship or shops.
+    pub fn surface_position(&self, column: u64, render_config: &RenderConfig) -> Vector3<f32> {
+        let surface_row =
+            generation::surface_row(self.seed, column, self.generation_chunk_dim().0, self.tile_count());
+
+        GlobalTileIndex(surface_row.saturating_sub(1), column % self.tile_count().1.max(1))
+            .to_translation(render_config)
     }
 
     /// The given chunk index gets clamped to the planet-dim by wrapping it in x-direction.
-    /// Returns none if the index is out of bounds in y-direction.
+    /// Returns an error if the index is out of bounds in y-direction.
     pub fn clamp_chunk_index(
         planet: &Planet,
         index: ChunkIndex,
@@ -94,126 +335,174 @@ impl Planet {
         #[cfg(feature = "trace")]
         trace!("clamping: {}, planet_dim.0: {}", index, planet.planet_dim.0);
 
-        let mut rv = index;
-        if rv.0 >= planet.planet_dim.0 {
+        if index.0 >= planet.planet_dim.0 || planet.planet_dim.1 == 0 {
             Err(GameWorldError::ChunkProblem(ChunkError::IndexOutOfBounds))
-        } else if rv.1 >= planet.planet_dim.1{
-            return Err(GameWorldError::ChunkProblem(ChunkError::IndexOutOfBounds));
         } else {
-            Ok(rv)
+            Ok(ChunkIndex(index.0, index.1 % planet.planet_dim.1))
         }
     }
 
-    /// Saves a chunk in the specified directory without removing it from the planet.
-    /// TODO: Save with less space-usage
-    pub fn save_chunk(&self, chunk_id: ChunkIndex, chunk_dir_path: PathBuf) {
-        let planet_width_in_chunks = self.planet_dim.0;
+    /// Stages a chunk for its region file in the specified directory without removing it from the planet,
+    /// see [`write_chunk_tiles`](struct.Planet.html#method.write_chunk_tiles).
+    /// Chunks that did not change since they were loaded or saved the last time are skipped,
+    /// see [`Chunk::is_dirty`](struct.Chunk.html#method.is_dirty).
+    /// Returns whether the chunk was staged. Once the transaction is committed,
+    /// staged chunks have to be [marked as saved](struct.Planet.html#method.mark_chunk_saved).
+    pub fn stage_chunk(
+        &self,
+        chunk_id: ChunkIndex,
+        chunk_dir_path: &Path,
+        transaction: &mut SaveTransaction,
+    ) -> Result<bool, GameWorldError> {
         match self.get_chunk(chunk_id) {
-            Ok(chunk_opt) => {
-                if let Some(chunk) = chunk_opt {
-                    let mut ser_chunk = ron::ser::Serializer::new(Some(Default::default()), true);
-                    //NOTE: Use this to save disk space!
-                    //let mut ser_chunk = ron::ser::Serializer::new(Some(Default::default()), false);
-                    {
-                        use serde::ser::SerializeMap;
-                        #[cfg(feature = "debug")]
-                        debug!("| serializing {:?}", chunk_id);
-
-                        if let Ok(mut serseq) = ser_chunk.serialize_map(None) {
-                            for (tile_index, tile_type) in chunk.iter_tiletypes() {
-                                if let Err(e) = serseq.serialize_key::<TileIndex>(&tile_index) {
-                                    error!(
-                                        "| Error serializing key of Tile {:?} in Chunk {:?}: {:?}",
-                                        tile_index, chunk_id, e
-                                    );
-                                }
-                                if let Err(e) = serseq.serialize_value::<TileTypes>(&tile_type) {
-                                    error!(
-                                        "| Error serializing value of Tile {:?} in Chunk {:?}: {:?}",
-                                        tile_index, chunk_id, e
-                                    );
-                                }                           }
-                            if let Err(e) = serseq.end() {
-                                error!(
-                                    "| Error ending serialize for chunk {:?}: {:?}",
-                                    chunk_id, e
-                                );
-                            }
-                        } else {
-                            error!("| Error starting serialize for chunk {:?}.", chunk_id);
-                        }
-                    }
-
-                    let mut chunk_file_path = chunk_dir_path.clone();
-                    chunk_file_path = chunk_file_path.join(Path::new(
-                        &{ (chunk_id.1 * planet_width_in_chunks + chunk_id.0) as u64 }.to_string(),
-                    ));
-                    chunk_file_path.set_extension("ron");
-
-                    if let Err(e) =
-                        fs::write(chunk_file_path.clone(), ser_chunk.into_output_string())
-                    {
-                        error!(
-                            "| Writing chunk {:?} at '{:?}' resulted in {:?}",
-                            chunk_id, chunk_file_path, e
-                        );
-                    }
-                } else {
+            Ok(Some(chunk)) => {
+                if !chunk.is_dirty() {
                     #[cfg(feature = "debug")]
-                    debug!("| Removing {:?} failed, since it was not found.", chunk_id);
+                    debug!("| Skipped saving unmodified {:?}.", chunk_id);
+                    return Ok(false);
                 }
+
+                #[cfg(feature = "debug")]
+                debug!("| serializing {:?}", chunk_id);
+                self.write_chunk_tiles(chunk_dir_path, chunk_id, chunk.tile_types(), transaction)
+                    .map(|_| true)
+                    .map_err(|e| {
+                        error!("| Saving {:?} failed: {:?}.", chunk_id, e);
+                        e
+                    })
+            }
+            Ok(None) => {
+                #[cfg(feature = "debug")]
+                debug!("| Saving {:?} failed, since it was not found.", chunk_id);
+                Ok(false)
             }
             Err(e) => {
                 error!("| Error getting chunk from planet: {:?}.", e);
+                Err(e)
             }
         }
     }
 
-    /// TODO: When save_chunk uses less-space-variant, custom deserializer needed.
+    /// Marks a chunk as equal to its stored version, after the transaction it was staged in got committed.
+    pub fn mark_chunk_saved(&mut self, chunk_id: ChunkIndex) {
+        if let Ok(clamped_id) = Self::clamp_chunk_index(&self, chunk_id) {
+            if let Some(chunk) = self.chunks.get_mut(&clamped_id) {
+                chunk.mark_saved();
+            }
+        }
+    }
+
+    /// Stages the tiles of all chunks that got [unloaded](struct.Planet.html#method.unload_chunk) without being saved,
+    /// see [`write_chunk_tiles`](struct.Planet.html#method.write_chunk_tiles).
+    /// Once the transaction is committed, they have to be [cleared](struct.Planet.html#method.clear_unsaved_chunks).
+    pub fn stage_unsaved_chunks(
+        &self,
+        chunk_dir_path: &Path,
+        transaction: &mut SaveTransaction,
+    ) -> Result<(), GameWorldError> {
+        for (&chunk_id, tiles) in self.unsaved_chunks.iter() {
+            #[cfg(feature = "debug")]
+            debug!("| serializing unloaded {:?}", chunk_id);
+            if let Err(e) = self.write_chunk_tiles(chunk_dir_path, chunk_id, tiles, transaction) {
+                error!("| Saving unloaded {:?} failed: {:?}.", chunk_id, e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the tiles of unloaded chunks, after the transaction they were staged in got committed.
+    pub fn clear_unsaved_chunks(&mut self) {
+        self.unsaved_chunks.clear();
+    }
+
+    /// Writes the tiles of unloaded chunks in their own [`SaveTransaction`](struct.SaveTransaction.html)
+    /// once there are too many of them, so exploring the planet does not keep all of it in memory.
+    /// These chunks are stored ahead of the session and the players, which only get stored by the next save.
+    /// If writing them fails, they are kept and written by a later call.
+    pub fn limit_unsaved_chunks(&mut self, paths: &SavegamePaths) -> Result<(), GameWorldError> {
+        if self.unsaved_chunks.len() < UNSAVED_CHUNK_LIMIT {
+            return Ok(());
+        }
+
+        let mut transaction = match SaveTransaction::new(&paths.savegame_dir_path) {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                error!("| Could not start writing unloaded chunks: {:?}.", e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+            }
+        };
+        self.stage_unsaved_chunks(&paths.chunk_dir_path, &mut transaction)?;
+        if let Err(e) = transaction.commit() {
+            error!("| Committing {} unloaded chunks failed: {:?}.", self.unsaved_chunks.len(), e);
+            return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+        }
+
+        #[cfg(feature = "debug")]
+        debug!("| Wrote {} unloaded chunks.", self.unsaved_chunks.len());
+        self.clear_unsaved_chunks();
+        Ok(())
+    }
+
+    /// Whether the given chunk was stored in the specified directory, in its region file or as a legacy chunk file,
+    /// or got unloaded without being saved. If not, it has to be generated.
+    /// Fails if its region file cannot be read, the chunk must not be generated anew then, lest the next save overwrites it.
+    pub fn is_chunk_stored(&self, chunk_dir_path: &Path, chunk_id: ChunkIndex) -> Result<bool, GameWorldError> {
+        if self.unsaved_chunks.contains_key(&chunk_id) {
+            return Ok(true);
+        }
+
+        let region_file_path =
+            SavegamePaths::region_file_path(chunk_dir_path, RegionIndex::of_chunk(chunk_id));
+        let in_region = match RegionFile::open(&region_file_path) {
+            Ok(Some(region)) => region.contains(chunk_id),
+            Ok(None) => false,
+            Err(e) => {
+                error!("| Could not open {:?}: {:?}.", region_file_path, e);
+                return Err(Self::region_error(e));
+            }
+        };
+
+        Ok(in_region
+            || SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, chunk_format::EXTENSION).is_file()
+            || SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, "ron").is_file())
+    }
+
+    /// Loads a chunk stored in the specified directory, see [`read_chunk_tiles`](struct.Planet.html#method.read_chunk_tiles),
+    /// and creates its tile entities. Chunks unloaded without being saved get their unsaved tiles instead, and stay dirty.
     pub fn load_chunk(
         &mut self,
         chunk_id: ChunkIndex,
-        chunk_file_path: PathBuf,
+        chunk_dir_path: &Path,
         storages: &mut TileGenerationStorages<'_>,
     ) {
         #[cfg(feature = "debug")]
         debug!("+------------");
         #[cfg(feature = "debug")]
-        debug!("| chunk_file_path: {:?}", chunk_file_path);
+        debug!("| Loading {:?} from {:?}", chunk_id, chunk_dir_path);
 
-        let file = match fs::File::open(&chunk_file_path) {
-            Ok(rv) => rv,
-            Err(e) => {
-                error!("| Could not open {:?}: {:?}.", chunk_file_path.clone(), e);
+        let unsaved_tiles = self.unsaved_chunks.get(&chunk_id).cloned();
+        let b_unsaved = unsaved_tiles.is_some();
+        let read_result = match unsaved_tiles {
+            Some(tiles) => Ok(Some(tiles)),
+            None => self
+                .read_chunk_tiles(chunk_dir_path, chunk_id)
+                .map(|tiles_opt| tiles_opt.map(Arc::new)),
+        };
+        let tiles = match read_result {
+            Ok(Some(tiles)) => tiles,
+            Ok(None) => {
+                error!("| {:?} is not stored in {:?}.", chunk_id, chunk_dir_path);
                 return;
             }
-        };
-
-        let tiles: BTreeMap<TileIndex, TileTypes> = match ron::de::from_reader(&file) {
-            Ok(tiles) => tiles,
             Err(e) => {
-                error!(
-                    "| Error deserializing {:?}: {:?}.",
-                    chunk_file_path.clone(),
-                    e
-                );
+                error!("| Error reading {:?}: {:?}.", chunk_id, e);
                 return;
             }
         };
 
         let mut resulting_chunk = Chunk::empty();
-        let base_transform = {
-            let render_config = &storages.render_config;
-            let mut transform = Transform::default();
-            transform.set_position(Vector3::new(
-                chunk_id.1 as f32
-                    * (self.chunk_dim.1 as f32 * render_config.tile_size.1),
-                chunk_id.0 as f32
-                    * (self.chunk_dim.0 as f32 * render_config.tile_size.0),
-                0.0,
-            ));
-            transform
-        };
+        let base_transform = self.chunk_base_transform(chunk_id, &storages.render_config);
 
         #[cfg(feature = "trace")]
         trace!(
@@ -239,6 +528,10 @@ impl Planet {
             }
         }
 
+        if b_unsaved {
+            resulting_chunk.mark_dirty();
+            self.unsaved_chunks.remove(&chunk_id);
+        }
         self.chunks.insert(chunk_id, resulting_chunk);
 
         #[cfg(feature = "debug")]
@@ -263,7 +556,7 @@ impl Planet {
                 #[cfg(feature = "debug")]
                 debug!("| Creating {:?}.", clamped_id);
 
-                let chunk = Chunk::new(&self, chunk_id, self.chunk_dim, storages);
+                let chunk = Chunk::new(&self, clamped_id, self.chunk_dim, storages);
                 self.chunks.insert(clamped_id, chunk);
             }
             Err(e) => {
@@ -272,6 +565,259 @@ impl Planet {
         };
     }
 
+    /// Changes the tile at the given position to the given type, e.g. after drilling or an explosion.
+    /// The old tile entity gets replaced by a new one, see [`Chunk::replace_tile`](struct.Chunk.html#method.replace_tile).
+    /// The tile's chunk has to be loaded, and the tile must not be
+    /// [indestructible](../../../entities/tile/enum.TileTypes.html#method.is_indestructible).
+    ///
+    /// Sends `TileEvent::Changed` on success, `TileEvent::FailedChange` otherwise.
+    /// Returns the type of the replaced tile.
+    pub fn set_tile(
+        &mut self,
+        global_tile: GlobalTileIndex,
+        tile_type: TileTypes,
+        // NOTE: This is pretty ugly
+        storages: &mut TileGenerationStorages<'_>,
+        tile_events: &mut EventChannel<TileEvent>,
+    ) -> Result<TileTypes, GameWorldError> {
+        let (chunk_id, tile_id) = global_tile.to_indices(self);
+
+        // Taken out of the map, so the planet can be borrowed while the chunk changes.
+        let result = match self.chunks.remove(&chunk_id) {
+            Some(mut chunk) => {
+                let old_type = chunk.get_tile_type(tile_id);
+                let result = match old_type {
+                    Some(old_type) if old_type.is_indestructible() => {
+                        Err(GameWorldError::TileProblem(TileError::Indestructible))
+                    }
+                    _ => Chunk::replace_tile(&self, &mut chunk, chunk_id, tile_id, tile_type, storages),
+                };
+                self.chunks.insert(chunk_id, chunk);
+                result
+            }
+            None => Err(GameWorldError::ChunkProblem(ChunkError::NotLoaded(chunk_id))),
+        };
+
+        match result {
+            Ok(old_type) => {
+                #[cfg(feature = "debug")]
+                debug!("| Changed {} from {:?} to {:?}.", global_tile, old_type, tile_type);
+                tile_events.single_write(TileEvent::Changed(global_tile, old_type, tile_type));
+            }
+            Err(e) => {
+                warn!("| Changing {} to {:?} failed: {:?}.", global_tile, tile_type, e);
+                tile_events.single_write(TileEvent::FailedChange(global_tile, tile_type));
+            }
+        }
+
+        result
+    }
+
+    /// Re-partitions all chunks stored in the given directory into chunks of the new dimension,
+    /// and adjusts the `chunk_dim` and `planet_dim` accordingly.
+    /// The new chunk dimension has to evenly divide the planet's [tile count](struct.Planet.html#method.tile_count).
+    ///
+    /// New chunks only partially covered by stored chunks get completed by generating the missing tiles.
+    /// The new region files and the removal of the old ones are staged in the given transaction,
+    /// which has to be committed together with the planet's new dimensions,
+    /// see [`GameSessionData::rechunk`](struct.GameSessionData.html#method.rechunk).
+    /// The tiles of chunks [unloaded](struct.Planet.html#method.unload_chunk) without being saved are included,
+    /// and get dropped afterwards.
+    /// If this fails, the planet stays as it was. All chunks have to be unloaded beforehand.
+    pub fn rechunk(
+        &mut self,
+        new_chunk_dim: (u64, u64),
+        chunk_dir_path: &Path,
+        tables: &WorldGenTables,
+        transaction: &mut SaveTransaction,
+    ) -> Result<(), GameWorldError> {
+        if !self.chunks.is_empty() {
+            error!("| Cannot re-chunk the planet while {} chunks are loaded.", self.chunks.len());
+            return Err(GameWorldError::ChunkProblem(ChunkError::StillLoaded));
+        }
+
+        let tile_count = self.tile_count();
+        if new_chunk_dim.0 == 0
+            || new_chunk_dim.1 == 0
+            || tile_count.0 % new_chunk_dim.0 != 0
+            || tile_count.1 % new_chunk_dim.1 != 0
+        {
+            error!(
+                "| Chunk dimension {:?} does not evenly divide the planet's {:?} tiles.",
+                new_chunk_dim, tile_count
+            );
+            return Err(GameWorldError::ChunkProblem(ChunkError::InvalidDimension));
+        }
+        if new_chunk_dim == self.chunk_dim {
+            return Ok(());
+        }
+
+        #[cfg(feature = "debug")]
+        debug!("| Re-chunking planet from {:?} to {:?}.", self.chunk_dim, new_chunk_dim);
+
+        // Read all stored tiles, unsaved ones are newer than their stored version.
+        let mut stored_tiles: BTreeMap<GlobalTileIndex, TileTypes> = BTreeMap::new();
+        for row in 0..self.planet_dim.0 {
+            for col in 0..self.planet_dim.1 {
+                let chunk_id = ChunkIndex(row, col);
+                let tiles = match self.unsaved_chunks.get(&chunk_id) {
+                    Some(tiles) => tiles.clone(),
+                    None => match self.read_chunk_tiles(chunk_dir_path, chunk_id)? {
+                        Some(tiles) => Arc::new(tiles),
+                        None => continue,
+                    },
+                };
+
+                for (&tile_id, &tile_type) in tiles.iter() {
+                    match GlobalTileIndex::from_indices(self, chunk_id, tile_id) {
+                        Ok(global_tile) => {
+                            stored_tiles.insert(global_tile, tile_type);
+                        }
+                        Err(e) => {
+                            warn!("| Skipping stored {:?} of {:?}: {:?}.", tile_id, chunk_id, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        let old_dims = (self.planet_dim, self.chunk_dim, self.generation_chunk_dim);
+        self.generation_chunk_dim = Some(self.generation_chunk_dim());
+        self.chunk_dim = new_chunk_dim;
+        self.planet_dim = (tile_count.0 / new_chunk_dim.0, tile_count.1 / new_chunk_dim.1);
+
+        if let Err(e) = self.stage_rechunked(chunk_dir_path, &stored_tiles, tables, transaction) {
+            self.planet_dim = old_dims.0;
+            self.chunk_dim = old_dims.1;
+            self.generation_chunk_dim = old_dims.2;
+            return Err(e);
+        }
+        // Staged in the new dimension, the transaction is committed together with the planet's dimensions.
+        self.unsaved_chunks.clear();
+
+        #[cfg(feature = "debug")]
+        debug!("| Re-chunked {} stored tiles.", stored_tiles.len());
+
+        Ok(())
+    }
+
+    /// Reads the tiles of a chunk stored in the specified directory, without loading the chunk.
+    /// Chunks are read from their [region file](region/index.html),
+    /// chunks stored in single files by older versions are read as well.
+    /// Returns `None` if the chunk was never saved.
+    pub fn read_chunk_tiles(
+        &self,
+        chunk_dir_path: &Path,
+        chunk_id: ChunkIndex,
+    ) -> Result<Option<BTreeMap<TileIndex, TileTypes>>, GameWorldError> {
+        let region_file_path =
+            SavegamePaths::region_file_path(chunk_dir_path, RegionIndex::of_chunk(chunk_id));
+        let region_bytes = match RegionFile::open(&region_file_path).and_then(|region_opt| match region_opt {
+            Some(mut region) => region.read_chunk(chunk_id),
+            None => Ok(None),
+        }) {
+            Ok(bytes_opt) => bytes_opt,
+            Err(e) => {
+                error!("| Could not read {:?} from {:?}: {:?}.", chunk_id, region_file_path, e);
+                return Err(Self::region_error(e));
+            }
+        };
+
+        let chunk_file_path =
+            SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, chunk_format::EXTENSION);
+        let bytes = match region_bytes {
+            Some(bytes) => Some(bytes),
+            None if chunk_file_path.is_file() => match fs::read(&chunk_file_path) {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    error!("| Could not read {:?}: {:?}.", chunk_file_path, e);
+                    return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+                }
+            },
+            None => None,
+        };
+
+        if let Some(bytes) = bytes {
+            return match chunk_format::decode(&bytes, self.chunk_dim) {
+                Ok(tiles) => Ok(Some(tiles)),
+                Err(e) => {
+                    error!("| Error decoding {:?}: {:?}.", chunk_id, e);
+                    Err(GameWorldError::ChunkProblem(ChunkError::Corrupt))
+                }
+            };
+        }
+
+        let legacy_file_path = SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, "ron");
+        if !legacy_file_path.is_file() {
+            return Ok(None);
+        }
+
+        let file = match fs::File::open(&legacy_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("| Could not open {:?}: {:?}.", legacy_file_path, e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+            }
+        };
+
+        match ron::de::from_reader(&file) {
+            Ok(tiles) => Ok(Some(tiles)),
+            Err(e) => {
+                error!("| Error deserializing {:?}: {:?}.", legacy_file_path, e);
+                Err(GameWorldError::ChunkProblem(ChunkError::Corrupt))
+            }
+        }
+    }
+
+    /// Writes the tiles of a chunk to its [region file](region/index.html) in the specified directory,
+    /// without the chunk having to be loaded. Single chunk files of older versions get removed.
+    /// Nothing changes on disk before the given transaction is committed.
+    /// Can be used for tiles created by [`generation::chunk_tiles`](generation/fn.chunk_tiles.html).
+    pub fn write_chunk_tiles(
+        &self,
+        chunk_dir_path: &Path,
+        chunk_id: ChunkIndex,
+        tiles: &BTreeMap<TileIndex, TileTypes>,
+        transaction: &mut SaveTransaction,
+    ) -> Result<(), GameWorldError> {
+        let bytes = match chunk_format::encode(self.chunk_dim, tiles) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("| Error encoding {:?}: {:?}.", chunk_id, e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Corrupt));
+            }
+        };
+
+        let region_file_path =
+            SavegamePaths::region_file_path(chunk_dir_path, RegionIndex::of_chunk(chunk_id));
+        let staged_file_path = match transaction.modify(&region_file_path) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("| Could not stage {:?}: {:?}.", region_file_path, e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+            }
+        };
+        if let Err(e) = RegionFile::open_or_create(&staged_file_path)
+            .and_then(|mut region| region.write_chunk(chunk_id, &bytes))
+        {
+            error!("| Writing {:?} to {:?} resulted in {:?}.", chunk_id, staged_file_path, e);
+            return Err(Self::region_error(e));
+        }
+
+        for outdated_file_path in [
+            SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, chunk_format::EXTENSION),
+            SavegamePaths::chunk_file_path(chunk_dir_path, chunk_id, "ron"),
+        ]
+        .iter()
+        {
+            if outdated_file_path.is_file() {
+                transaction.remove(outdated_file_path);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Drains all chunks currently stored in planet, useful when `save & exit` happens.
     pub fn drain_chunks(&mut self) -> hash_map::Drain<'_, ChunkIndex, Chunk> {
         #[cfg(feature = "debug")]
@@ -298,6 +844,27 @@ impl Planet {
         }
     }
 
+    /// Removes a `Chunk` from the world and destroys all its `Tile`s, like [`delete_chunk`](struct.Planet.html#method.delete_chunk).
+    /// If the chunk changed since it was loaded or saved, its tiles are kept until the session gets saved the next time,
+    /// see [`stage_unsaved_chunks`](struct.Planet.html#method.stage_unsaved_chunks),
+    /// or until [too many](struct.Planet.html#method.limit_unsaved_chunks) are kept.
+    pub fn unload_chunk(
+        &mut self,
+        index: ChunkIndex,
+        entities: &Read<'_, EntitiesRes, DefaultProvider>,
+    ) {
+        let unsaved_tiles = match self.chunks.get(&index) {
+            Some(chunk) if chunk.is_dirty() => Some(chunk.tile_types().clone()),
+            _ => None,
+        };
+        if let Some(tiles) = unsaved_tiles {
+            #[cfg(feature = "debug")]
+            debug!("| Keeping the unsaved tiles of {:?}.", index);
+            self.unsaved_chunks.insert(index, Arc::new(tiles));
+        }
+        self.delete_chunk(index, entities);
+    }
+
     /// Returns an iterator over all chunks currently stored in planet
     /// mapping `ChunkIndex <-> Chunk`.
     pub fn iter_chunks(&self) -> hash_map::Iter<'_, ChunkIndex, Chunk> {
@@ -309,4 +876,81 @@ impl Planet {
     pub fn clear_chunks(&mut self) {
         self.chunks.clear();
     }
-}
\ No newline at end of file
+}
+
+// private methods
+impl Planet {
+    // Problems with region files, as far as the planet is concerned. Details get logged by the caller.
+    fn region_error(e: RegionError) -> GameWorldError {
+        match e {
+            RegionError::Io(_) => GameWorldError::ChunkProblem(ChunkError::Io),
+            RegionError::IndexOutOfBounds(_) => GameWorldError::ChunkProblem(ChunkError::IndexOutOfBounds),
+            _ => GameWorldError::ChunkProblem(ChunkError::Corrupt),
+        }
+    }
+
+    // Stages the stored tiles as chunks of the current dimension, replacing all files in the chunk directory.
+    // Tiles not found in `stored_tiles` get generated.
+    fn stage_rechunked(
+        &self,
+        chunk_dir_path: &Path,
+        stored_tiles: &BTreeMap<GlobalTileIndex, TileTypes>,
+        tables: &WorldGenTables,
+        transaction: &mut SaveTransaction,
+    ) -> Result<(), GameWorldError> {
+        let new_chunks: BTreeSet<ChunkIndex> = stored_tiles
+            .keys()
+            .map(|global_tile| global_tile.chunk_index(self))
+            .collect();
+
+        // Region files of the old dimension must not be copied, their chunks overlap the new ones.
+        let mut region_file_paths: Vec<PathBuf> = Vec::new();
+        for &chunk_id in new_chunks.iter() {
+            let region_file_path =
+                SavegamePaths::region_file_path(chunk_dir_path, RegionIndex::of_chunk(chunk_id));
+            if region_file_paths.contains(&region_file_path) {
+                continue;
+            }
+            if let Err(e) = transaction.create(&region_file_path) {
+                error!("| Could not stage {:?}: {:?}.", region_file_path, e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+            }
+            region_file_paths.push(region_file_path);
+        }
+
+        for &chunk_id in new_chunks.iter() {
+            let mut tiles: BTreeMap<TileIndex, TileTypes> = BTreeMap::new();
+            for y in 0..self.chunk_dim.0 {
+                for x in 0..self.chunk_dim.1 {
+                    let tile_id = TileIndex(y, x);
+                    let global_tile = GlobalTileIndex::from_indices(self, chunk_id, tile_id)?;
+                    let tile_type = match stored_tiles.get(&global_tile) {
+                        Some(&tile_type) => tile_type,
+                        None => generation::tile_type(self, tables, chunk_id, tile_id),
+                    };
+                    tiles.insert(tile_id, tile_type);
+                }
+            }
+
+            self.write_chunk_tiles(chunk_dir_path, chunk_id, &tiles, transaction)?;
+        }
+
+        // Everything else stored in the old dimension, including chunk files of older versions.
+        let entries = match fs::read_dir(chunk_dir_path) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                error!("| Could not list {:?}: {:?}.", chunk_dir_path, e);
+                return Err(GameWorldError::ChunkProblem(ChunkError::Io));
+            }
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let b_staged = path.extension().map_or(false, |extension| extension == "tmp");
+            if path.is_file() && !b_staged && !region_file_paths.contains(&path) {
+                transaction.remove(&path);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,484 @@
+//! Region files bundle the chunks of a planet, so big planets do not create thousands of tiny files.
+//!
+//! A region holds `REGION_DIM` chunks, the chunk data itself is stored in the [chunk format](../chunk_format/index.html).
+//! Every region file starts with a header, all numbers are little endian:
+//!
+//! | bytes | content                                            |
+//! |-------|----------------------------------------------------|
+//! | 4     | magic number `TDRG`                                |
+//! | 2     | format version                                     |
+//! | 4 + 4 | region dim in chunks (rows, columns)               |
+//! | 12 ×  | one table entry per chunk, row by row:             |
+//! |       | offset, capacity and length of its data, in bytes  |
+//!
+//! A chunk missing in the region has an offset of 0.
+//! Each chunk gets a slot of whole sectors, so rewriting a chunk happens in place, as long as it still fits.
+//! Otherwise it is moved to the first gap large enough, or appended.
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::ChunkIndex;
+
+/// The version of the region format written by [`RegionFile`](struct.RegionFile.html).
+pub const FORMAT_VERSION: u16 = 1;
+/// The extension of region files.
+pub const EXTENSION: &str = "region";
+/// The amount of chunks stored in one region (rows, columns).
+pub const REGION_DIM: (u64, u64,) = (8, 8,);
+
+const MAGIC: &[u8; 4] = b"TDRG";
+const HEADER_LEN: u64 = 14;
+const ENTRY_LEN: u64 = 12;
+// Chunk slots are allocated in multiples of this, so slightly larger chunks can be rewritten in place.
+const SECTOR_LEN: u64 = 256;
+
+/// Problems reading or writing region files.
+#[derive(PartialEq, Eq, Clone, Debug,)]
+pub enum RegionError {
+    /// Reading or writing the file failed.
+    Io(io::ErrorKind,),
+    /// The file does not start with the magic number, so it is no region file.
+    InvalidMagic,
+    /// The region was written by a newer version of the game.
+    UnsupportedVersion(u16,),
+    /// The region dim stored in the header differs from `REGION_DIM`.
+    DimensionMismatch((u64, u64,),),
+    /// The chunk is not part of this region.
+    IndexOutOfBounds(ChunkIndex,),
+    /// An entry of the offset table points outside of the file.
+    InvalidTable,
+    /// The chunk data is too large for the offset table.
+    TooLarge,
+}
+
+impl From<io::Error,> for RegionError {
+    fn from(e: io::Error,) -> Self {
+        RegionError::Io(e.kind(),)
+    }
+}
+
+/// The index of the region containing a chunk (rows, columns).
+#[derive(PartialEq, Eq, Copy, Clone, PartialOrd, Ord, Hash, Debug,)]
+pub struct RegionIndex(pub u64, pub u64,);
+
+impl RegionIndex {
+    /// The region containing the given chunk.
+    pub fn of_chunk(chunk_id: ChunkIndex,) -> RegionIndex {
+        RegionIndex(chunk_id.0 / REGION_DIM.0, chunk_id.1 / REGION_DIM.1,)
+    }
+
+    /// The amount of regions needed for a planet of the given dimension (rows, columns).
+    pub fn region_count(planet_dim: (u64, u64,),) -> (u64, u64,) {
+        (
+            (planet_dim.0 + REGION_DIM.0 - 1) / REGION_DIM.0,
+            (planet_dim.1 + REGION_DIM.1 - 1) / REGION_DIM.1,
+        )
+    }
+
+    /// The index of the chunk inside of its region.
+    pub fn local_chunk_index(chunk_id: ChunkIndex,) -> ChunkIndex {
+        ChunkIndex(chunk_id.0 % REGION_DIM.0, chunk_id.1 % REGION_DIM.1,)
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default,)]
+struct TableEntry {
+    offset:   u64,
+    capacity: u64,
+    length:   u64,
+}
+
+/// An opened region file, giving random access to the chunks it contains.
+/// The offset table is read once when opening, chunk data only on request.
+pub struct RegionFile {
+    file:  fs::File,
+    table: Vec<TableEntry,>,
+}
+
+impl RegionFile {
+    /// Opens an existing region file for reading. Returns `None` if the file does not exist.
+    pub fn open(path: &Path,) -> Result<Option<RegionFile,>, RegionError,> {
+        if !path.is_file() {
+            return Ok(None,);
+        }
+
+        let file = fs::File::open(path,)?;
+        Self::from_file(file,).map(Some,)
+    }
+
+    /// Opens a region file for reading and writing, creating an empty one if it does not exist yet.
+    pub fn open_or_create(path: &Path,) -> Result<RegionFile, RegionError,> {
+        let mut file = fs::OpenOptions::new()
+            .read(true,)
+            .write(true,)
+            .create(true,)
+            .open(path,)?;
+
+        if file.metadata()?.len() == 0 {
+            let table_len = (REGION_DIM.0 * REGION_DIM.1 * ENTRY_LEN) as usize;
+            let mut header = Vec::with_capacity(HEADER_LEN as usize + table_len,);
+            header.extend_from_slice(MAGIC,);
+            write_le(&mut header, u64::from(FORMAT_VERSION,), 2,);
+            write_le(&mut header, REGION_DIM.0, 4,);
+            write_le(&mut header, REGION_DIM.1, 4,);
+            header.resize(HEADER_LEN as usize + table_len, 0,);
+            file.write_all(&header,)?;
+        }
+
+        Self::from_file(file,)
+    }
+
+    /// Whether the region contains the given chunk, using the chunk's index on the planet.
+    pub fn contains(&self, chunk_id: ChunkIndex,) -> bool {
+        self.entry_index(chunk_id,)
+            .map(|index| self.table[index].offset != 0,)
+            .unwrap_or(false,)
+    }
+
+    /// Reads the data of the given chunk, using the chunk's index on the planet.
+    /// Returns `None` if the chunk is not stored in this region.
+    pub fn read_chunk(&mut self, chunk_id: ChunkIndex,) -> Result<Option<Vec<u8,>,>, RegionError,> {
+        let entry = self.table[self.entry_index(chunk_id,)?];
+        if entry.offset == 0 {
+            return Ok(None,);
+        }
+
+        let mut rv = vec![0; entry.length as usize];
+        self.file.seek(SeekFrom::Start(entry.offset,),)?;
+        self.file.read_exact(&mut rv,)?;
+        Ok(Some(rv,),)
+    }
+
+    /// Writes the data of the given chunk, using the chunk's index on the planet.
+    /// Overwrites the chunk's slot in place if the data fits, otherwise moves the chunk.
+    pub fn write_chunk(&mut self, chunk_id: ChunkIndex, data: &[u8],) -> Result<(), RegionError,> {
+        let index = self.entry_index(chunk_id,)?;
+        let length = data.len() as u64;
+        if length > u64::from(u32::max_value(),) {
+            return Err(RegionError::TooLarge,);
+        }
+
+        let mut entry = self.table[index];
+        if entry.offset == 0 || entry.capacity < length {
+            let capacity = ((length + SECTOR_LEN - 1) / SECTOR_LEN).max(1,) * SECTOR_LEN;
+            // The old slot stays reserved until the entry points to the new one, so its data is never overwritten early.
+            entry.offset = self.free_offset(capacity,)?;
+            entry.capacity = capacity;
+            if entry.offset + capacity > u64::from(u32::max_value(),) {
+                return Err(RegionError::TooLarge,);
+            }
+        }
+        entry.length = length;
+
+        self.file.seek(SeekFrom::Start(entry.offset,),)?;
+        self.file.write_all(data,)?;
+        // Pads the slot, so the file always covers the whole capacity of its last slot.
+        let padding = entry.capacity - length;
+        if padding > 0 {
+            self.file.write_all(&vec![0; padding as usize],)?;
+        }
+
+        self.table[index] = entry;
+        self.write_entry(index,)
+    }
+
+    /// Removes the given chunk from the region, so it gets generated again.
+    /// Its slot is reused by later writes.
+    pub fn remove_chunk(&mut self, chunk_id: ChunkIndex,) -> Result<(), RegionError,> {
+        let index = self.entry_index(chunk_id,)?;
+        self.table[index] = TableEntry::default();
+        self.write_entry(index,)
+    }
+
+    /// Writes all changes to disk.
+    pub fn sync(&mut self,) -> Result<(), RegionError,> {
+        self.file.sync_all()?;
+        Ok((),)
+    }
+}
+
+// private methods
+impl RegionFile {
+    fn from_file(mut file: fs::File,) -> Result<RegionFile, RegionError,> {
+        let file_len = file.metadata()?.len();
+        let mut header = [0u8; HEADER_LEN as usize];
+        file.seek(SeekFrom::Start(0,),)?;
+        if file.read_exact(&mut header,).is_err() || &header[0 .. 4] != MAGIC {
+            return Err(RegionError::InvalidMagic,);
+        }
+
+        let version = read_le(&header[4 .. 6],) as u16;
+        if version != FORMAT_VERSION {
+            return Err(RegionError::UnsupportedVersion(version,),);
+        }
+        let region_dim = (read_le(&header[6 .. 10],), read_le(&header[10 .. 14],),);
+        if region_dim != REGION_DIM {
+            return Err(RegionError::DimensionMismatch(region_dim,),);
+        }
+
+        let entry_count = (REGION_DIM.0 * REGION_DIM.1) as usize;
+        let mut table_bytes = vec![0u8; entry_count * ENTRY_LEN as usize];
+        file.read_exact(&mut table_bytes,)
+            .map_err(|_| RegionError::InvalidTable,)?;
+
+        let data_start = HEADER_LEN + entry_count as u64 * ENTRY_LEN;
+        let mut table = Vec::with_capacity(entry_count,);
+        for bytes in table_bytes.chunks(ENTRY_LEN as usize,) {
+            let entry = TableEntry {
+                offset:   read_le(&bytes[0 .. 4],),
+                capacity: read_le(&bytes[4 .. 8],),
+                length:   read_le(&bytes[8 .. 12],),
+            };
+            let is_valid = entry.offset == 0
+                || (entry.offset >= data_start
+                    && entry.length <= entry.capacity
+                    && entry.offset + entry.capacity <= file_len);
+            if !is_valid {
+                return Err(RegionError::InvalidTable,);
+            }
+            table.push(entry,);
+        }
+
+        Ok(RegionFile {
+            file,
+            table,
+        },)
+    }
+
+    // The position of the chunk in the offset table.
+    fn entry_index(&self, chunk_id: ChunkIndex,) -> Result<usize, RegionError,> {
+        let local_id = RegionIndex::local_chunk_index(chunk_id,);
+        let index = (local_id.0 * REGION_DIM.1 + local_id.1) as usize;
+        if index < self.table.len() {
+            Ok(index,)
+        }
+        else {
+            Err(RegionError::IndexOutOfBounds(chunk_id,),)
+        }
+    }
+
+    // The first offset with `capacity` unused bytes, either a gap between slots or the end of the file.
+    fn free_offset(&self, capacity: u64,) -> Result<u64, RegionError,> {
+        let mut slots: Vec<(u64, u64,),> = self
+            .table
+            .iter()
+            .filter(|entry| entry.offset != 0,)
+            .map(|entry| (entry.offset, entry.offset + entry.capacity,),)
+            .collect();
+        slots.sort();
+
+        let mut offset = HEADER_LEN + self.table.len() as u64 * ENTRY_LEN;
+        for (start, end,) in slots {
+            if start >= offset + capacity {
+                return Ok(offset,);
+            }
+            offset = offset.max(end,);
+        }
+        Ok(offset,)
+    }
+
+    fn write_entry(&mut self, index: usize,) -> Result<(), RegionError,> {
+        let entry = self.table[index];
+        let mut bytes = Vec::with_capacity(ENTRY_LEN as usize,);
+        write_le(&mut bytes, entry.offset, 4,);
+        write_le(&mut bytes, entry.capacity, 4,);
+        write_le(&mut bytes, entry.length, 4,);
+
+        self.file
+            .seek(SeekFrom::Start(HEADER_LEN + index as u64 * ENTRY_LEN,),)?;
+        self.file.write_all(&bytes,)?;
+        Ok((),)
+    }
+}
+
+// Writes the lowest `byte_count` bytes of the value, little endian.
+fn write_le(bytes: &mut Vec<u8,>, value: u64, byte_count: usize,) {
+    for i in 0 .. byte_count {
+        bytes.push((value >> (8 * i)) as u8,);
+    }
+}
+
+// Reads a little endian number of up to 8 bytes.
+fn read_le(bytes: &[u8],) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &byte| acc << 8 | u64::from(byte,),)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf, process};
+
+    use super::*;
+
+    // A fresh directory for the region files of one test.
+    fn temp_dir(test_name: &str,) -> PathBuf {
+        let dir_path =
+            env::temp_dir().join(format!("toppa_region_{}_{}", test_name, process::id()),);
+        let _ = fs::remove_dir_all(&dir_path,);
+        fs::create_dir_all(&dir_path,).unwrap();
+        dir_path
+    }
+
+    fn offset_of(region: &RegionFile, chunk_id: ChunkIndex,) -> u64 {
+        region.table[region.entry_index(chunk_id,).unwrap()].offset
+    }
+
+    #[test]
+    fn writes_reads_and_removes_chunks() {
+        let dir_path = temp_dir("write_read_remove",);
+        let mut region = RegionFile::open_or_create(&dir_path.join("r0_c0.region",),).unwrap();
+        let (first, second,) = (ChunkIndex(0, 0,), ChunkIndex(3, 5,),);
+
+        assert!(!region.contains(first));
+        assert_eq!(region.read_chunk(first), Ok(None));
+
+        region.write_chunk(first, b"first chunk",).unwrap();
+        region.write_chunk(second, b"second chunk",).unwrap();
+        assert!(region.contains(first));
+        assert_eq!(region.read_chunk(first), Ok(Some(b"first chunk".to_vec())));
+        assert_eq!(
+            region.read_chunk(second),
+            Ok(Some(b"second chunk".to_vec()))
+        );
+
+        region.remove_chunk(first,).unwrap();
+        assert!(!region.contains(first));
+        assert_eq!(region.read_chunk(first), Ok(None));
+        assert_eq!(
+            region.read_chunk(second),
+            Ok(Some(b"second chunk".to_vec()))
+        );
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn uses_the_index_inside_of_the_region() {
+        let dir_path = temp_dir("local_index",);
+        let mut region = RegionFile::open_or_create(&dir_path.join("r1_c1.region",),).unwrap();
+        let chunk_id = ChunkIndex(REGION_DIM.0 + 2, REGION_DIM.1 + 7,);
+
+        region.write_chunk(chunk_id, b"chunk",).unwrap();
+        assert!(region.contains(ChunkIndex(2, 7,)));
+        assert_eq!(RegionIndex::of_chunk(chunk_id), RegionIndex(1, 1,));
+        assert_eq!(
+            RegionIndex::region_count((REGION_DIM.0 + 1, REGION_DIM.1,)),
+            (2, 1,)
+        );
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn rewrites_chunks_in_place_while_they_fit() {
+        let dir_path = temp_dir("in_place",);
+        let mut region = RegionFile::open_or_create(&dir_path.join("r0_c0.region",),).unwrap();
+        let (chunk_id, neighbour,) = (ChunkIndex(0, 0,), ChunkIndex(0, 1,),);
+
+        region.write_chunk(chunk_id, &[1; 100],).unwrap();
+        region.write_chunk(neighbour, &[2; 100],).unwrap();
+        let offset = offset_of(&region, chunk_id,);
+
+        // Grows, but still fits into its sector.
+        region
+            .write_chunk(chunk_id, &[3; SECTOR_LEN as usize],)
+            .unwrap();
+        assert_eq!(offset_of(&region, chunk_id), offset);
+
+        // Outgrows its sector, and gets moved behind the neighbour.
+        region
+            .write_chunk(chunk_id, &[4; SECTOR_LEN as usize + 1],)
+            .unwrap();
+        let moved_offset = offset_of(&region, chunk_id,);
+        assert!(moved_offset > offset_of(&region, neighbour));
+        assert_eq!(
+            region.read_chunk(chunk_id),
+            Ok(Some(vec![4; SECTOR_LEN as usize + 1]))
+        );
+
+        // Shrinks in place.
+        region.write_chunk(chunk_id, &[5; 10],).unwrap();
+        assert_eq!(offset_of(&region, chunk_id), moved_offset);
+        assert_eq!(region.read_chunk(chunk_id), Ok(Some(vec![5; 10])));
+        assert_eq!(region.read_chunk(neighbour), Ok(Some(vec![2; 100])));
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn reuses_freed_sectors() {
+        let dir_path = temp_dir("reuse",);
+        let mut region = RegionFile::open_or_create(&dir_path.join("r0_c0.region",),).unwrap();
+        let (first, second, third,) = (ChunkIndex(0, 0,), ChunkIndex(0, 1,), ChunkIndex(0, 2,),);
+
+        region.write_chunk(first, &[1; 10],).unwrap();
+        region.write_chunk(second, &[2; 10],).unwrap();
+        let freed_offset = offset_of(&region, first,);
+        let file_len = fs::metadata(dir_path.join("r0_c0.region",),).unwrap().len();
+
+        region.remove_chunk(first,).unwrap();
+        region.write_chunk(third, &[3; 10],).unwrap();
+        assert_eq!(offset_of(&region, third), freed_offset);
+        assert_eq!(
+            fs::metadata(dir_path.join("r0_c0.region",)).unwrap().len(),
+            file_len
+        );
+
+        // Too large for the gap, so it gets appended.
+        region.remove_chunk(third,).unwrap();
+        region
+            .write_chunk(third, &[3; SECTOR_LEN as usize + 1],)
+            .unwrap();
+        assert_eq!(offset_of(&region, third), file_len);
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn reopens_written_regions() {
+        let dir_path = temp_dir("reopen",);
+        let region_path = dir_path.join("r0_c0.region",);
+        assert!(RegionFile::open(&region_path,).unwrap().is_none());
+
+        {
+            let mut region = RegionFile::open_or_create(&region_path,).unwrap();
+            region.write_chunk(ChunkIndex(1, 2,), b"stored",).unwrap();
+            region.sync().unwrap();
+        }
+
+        let mut region = RegionFile::open(&region_path,).unwrap().unwrap();
+        assert!(region.contains(ChunkIndex(1, 2,)));
+        assert!(!region.contains(ChunkIndex(2, 1,)));
+        assert_eq!(
+            region.read_chunk(ChunkIndex(1, 2,)),
+            Ok(Some(b"stored".to_vec()))
+        );
+
+        // Opening it for writing keeps the stored chunks as well.
+        let mut region = RegionFile::open_or_create(&region_path,).unwrap();
+        assert_eq!(
+            region.read_chunk(ChunkIndex(1, 2,)),
+            Ok(Some(b"stored".to_vec()))
+        );
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn refuses_other_files() {
+        let dir_path = temp_dir("invalid",);
+        let region_path = dir_path.join("r0_c0.region",);
+        fs::write(&region_path, b"no region file at all",).unwrap();
+        assert_eq!(
+            RegionFile::open(&region_path,).err(),
+            Some(RegionError::InvalidMagic)
+        );
+
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+}
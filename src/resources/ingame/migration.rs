@@ -0,0 +1,528 @@
+//! Upgrades savegames written by older versions of the game.
+//!
+//! Every persisted file carries a format version: `session_data.ron` its `format_version` field,
+//! chunk and region files a version in their header.
+//! `players.ron` has no version of its own, it is covered by the `format_version` of `session_data.ron`:
+//! both are always committed in the same transaction, so changing the format of the players
+//! requires a new `SAVEGAME_VERSION` and a migration rewriting `players.ron`.
+//! Older savegames get upgraded on load one version at a time by the steps registered in `MIGRATIONS`,
+//! savegames of unknown, newer versions are refused before anything gets loaded.
+use std::{fmt, fs, path::PathBuf};
+
+use crate::resources::RenderConfig;
+
+use super::{
+    game_world::{chunk_format, region, ChunkIndex, Planet},
+    SaveTransaction, SavegamePaths,
+};
+
+/// The version of the savegame format written by this version of the game.
+/// Savegames without a version are version 0.
+pub const SAVEGAME_VERSION: u32 = 2;
+
+/// Problems loading or upgrading a savegame, meant to be shown to the player.
+#[derive(PartialEq, Eq, Clone, Debug,)]
+pub enum SavegameError {
+    /// No savegame of this name exists.
+    NotFound(String,),
+    /// A file of the savegame could not be read or written.
+    Io(PathBuf, String,),
+    /// The session data could not be deserialized.
+    Corrupt(PathBuf, String,),
+    /// The savegame was written by a newer version of the game.
+    UnsupportedVersion { found: u32, supported: u32, },
+    /// The savegame was written by an older version of the game, and has to be upgraded by loading it in the game.
+    /// Only refused where savegames are read without being changed.
+    Outdated { found: u32, current: u32, },
+    /// A save of the savegame was interrupted, and has to be finished or discarded by loading it in the game.
+    /// Only refused where savegames are read without being changed.
+    UnfinishedSave(PathBuf,),
+    /// A chunk or region file was written by a newer version of the game.
+    UnsupportedFileVersion {
+        path:      PathBuf,
+        found:     u16,
+        supported: u16,
+    },
+    /// No migration upgrades savegames of this version.
+    MissingMigration(u32,),
+    /// Upgrading the savegame from this version failed.
+    MigrationFailed { from: u32, reason: String, },
+}
+
+impl fmt::Display for SavegameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match *self {
+            SavegameError::NotFound(ref name,) => write!(f, "No savegame named {:?} exists.", name),
+            SavegameError::Io(ref path, ref e,) => write!(f, "Could not access {:?}: {}.", path, e),
+            SavegameError::Corrupt(ref path, ref e,) => {
+                write!(f, "The savegame file {:?} is damaged: {}.", path, e)
+            },
+            SavegameError::UnsupportedVersion { found, supported, } => write!(
+                f,
+                "The savegame has format version {}, but this version of the game only supports up to {}. \
+                 Please update the game.",
+                found, supported
+            ),
+            SavegameError::Outdated { found, current, } => write!(
+                f,
+                "The savegame has format version {}, load it in the game once to upgrade it to version {}.",
+                found, current
+            ),
+            SavegameError::UnfinishedSave(ref path,) => write!(
+                f,
+                "The last save of {:?} was interrupted, load it in the game once to finish it.",
+                path
+            ),
+            SavegameError::UnsupportedFileVersion {
+                ref path,
+                found,
+                supported,
+            } => write!(
+                f,
+                "{:?} has format version {}, but this version of the game only supports up to {}. \
+                 Please update the game.",
+                path, found, supported
+            ),
+            SavegameError::MissingMigration(version,) => {
+                write!(f, "Savegames of format version {} cannot be upgraded.", version)
+            },
+            SavegameError::MigrationFailed { from, ref reason, } => write!(
+                f,
+                "Upgrading the savegame from format version {} failed: {}.",
+                from, reason
+            ),
+        }
+    }
+}
+
+/// One step of upgrading a savegame, from version `from` to `from + 1`.
+/// Gets the session data as RON text, since older versions might not deserialize into the current structs.
+/// A load may be interrupted after a step, so steps have to cope with running again.
+struct Migration {
+    from:        u32,
+    description: &'static str,
+    migrate:     fn(&mut String, &SavegamePaths,) -> Result<(), String,>,
+}
+
+/// All migrations, ordered by the version they upgrade from.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from:        0,
+        description:
+            "rename chunk files to r{row}_c{col} and move single chunk files into region files",
+        migrate:     migrate_0_to_1,
+    },
+    Migration {
+        from:        1,
+        description:
+            "players are stored in players.ron, savegames without it start with a new ship",
+        migrate:     migrate_1_to_2,
+    },
+];
+
+/// The format version of the given session data, `0` if it has none.
+/// Read without deserializing, since the session data of other versions might not deserialize.
+pub fn session_format_version(session_text: &str,) -> Result<u32, String,> {
+    match ron_field(session_text, "format_version",) {
+        Some(value,) => {
+            value
+                .parse::<u32>()
+                .map_err(|e| format!("Invalid format version {:?}: {}", value, e),)
+        },
+        None => Ok(0,),
+    }
+}
+
+/// Upgrades the given session data and the chunk files of the savegame step by step to `SAVEGAME_VERSION`.
+/// Returns the version the savegame had before, so the caller knows whether to store the upgraded session data.
+pub fn migrate_savegame(
+    session_text: &mut String,
+    paths: &SavegamePaths,
+) -> Result<u32, SavegameError,> {
+    let found = session_format_version(session_text,)
+        .map_err(|e| SavegameError::Corrupt(paths.savegame_file_path.clone(), e,),)?;
+    if found > SAVEGAME_VERSION {
+        return Err(SavegameError::UnsupportedVersion {
+            found,
+            supported: SAVEGAME_VERSION,
+        },);
+    }
+
+    // Checked first, so migrations do not trip over files they cannot read.
+    check_chunk_versions(paths,)?;
+
+    for version in found .. SAVEGAME_VERSION {
+        let migration = match MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == version,)
+        {
+            Some(migration,) => migration,
+            None => return Err(SavegameError::MissingMigration(version,),),
+        };
+
+        #[cfg(feature = "debug")]
+        debug!(
+            "| Migrating savegame from version {}: {}.",
+            version, migration.description
+        );
+        (migration.migrate)(session_text, paths,).map_err(|reason| {
+            SavegameError::MigrationFailed {
+                from:   version,
+                reason: format!("{} ({})", reason, migration.description),
+            }
+        },)?;
+    }
+
+    Ok(found,)
+}
+
+/// Refuses savegames containing chunk or region files of a newer version,
+/// reading only their headers.
+fn check_chunk_versions(paths: &SavegamePaths,) -> Result<(), SavegameError,> {
+    let entries = match fs::read_dir(&paths.chunk_dir_path,) {
+        Ok(entries,) => entries,
+        Err(e,) => {
+            return Err(SavegameError::Io(
+                paths.chunk_dir_path.clone(),
+                e.to_string(),
+            ),)
+        },
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok(),)
+        .map(|entry| entry.path(),)
+    {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned(),);
+        let found = match extension.as_ref().map(|extension| extension.as_str(),) {
+            Some(region::EXTENSION,) => {
+                match region::RegionFile::open(&path,) {
+                    Err(region::RegionError::UnsupportedVersion(found,),) => {
+                        Some((found, region::FORMAT_VERSION,),)
+                    },
+                    _ => None,
+                }
+            },
+            Some(chunk_format::EXTENSION,) => {
+                let bytes = fs::read(&path,)
+                    .map_err(|e| SavegameError::Io(path.clone(), e.to_string(),),)?;
+                match chunk_format::format_version(&bytes,) {
+                    Ok(found,) if found > chunk_format::FORMAT_VERSION => {
+                        Some((found, chunk_format::FORMAT_VERSION,),)
+                    },
+                    _ => None,
+                }
+            },
+            _ => None,
+        };
+
+        if let Some((found, supported,),) = found {
+            return Err(SavegameError::UnsupportedFileVersion {
+                path,
+                found,
+                supported,
+            },);
+        }
+    }
+
+    Ok((),)
+}
+
+// Version 0 savegames named their chunk files `col * planet_dim.0 + row`,
+// and stored every chunk in a single RON file.
+fn migrate_0_to_1(session_text: &mut String, paths: &SavegamePaths,) -> Result<(), String,> {
+    let planet_dim = ron_dim_field(session_text, "planet_dim",)?;
+    let chunk_dim = ron_dim_field(session_text, "chunk_dim",)?;
+
+    paths
+        .migrate_file_names(planet_dim,)
+        .map_err(|_| "Renaming the chunk files failed".to_string(),)?;
+
+    // Only used to read and write tiles, which neither needs the seed nor any loaded chunk.
+    let planet = Planet::new(planet_dim, chunk_dim, 0, &RenderConfig::default(),);
+    let mut transaction = SaveTransaction::new(&paths.savegame_dir_path,)
+        .map_err(|e| format!("Starting to move the chunks failed: {}", e),)?;
+    for row in 0 .. planet_dim.0 {
+        for col in 0 .. planet_dim.1 {
+            let chunk_id = ChunkIndex(row, col,);
+            if !SavegamePaths::chunk_file_path(&paths.chunk_dir_path, chunk_id, "ron",).is_file() {
+                continue;
+            }
+
+            // Writing the tiles removes the single chunk file.
+            match planet.read_chunk_tiles(&paths.chunk_dir_path, chunk_id,) {
+                Ok(Some(tiles,),) => {
+                    planet
+                        .write_chunk_tiles(
+                            &paths.chunk_dir_path,
+                            chunk_id,
+                            &tiles,
+                            &mut transaction,
+                        )
+                        .map_err(|e| {
+                            format!("Moving {:?} into its region failed: {:?}", chunk_id, e)
+                        },)?
+                },
+                Ok(None,) => {},
+                Err(e,) => return Err(format!("Reading {:?} failed: {:?}", chunk_id, e),),
+            }
+        }
+    }
+
+    transaction
+        .commit()
+        .map_err(|e| format!("Committing the moved chunks failed: {}", e),)
+}
+
+// Version 1 savegames did not store players, nothing to convert.
+// Loading them spawns a new ship, which gets stored with the next save.
+fn migrate_1_to_2(_session_text: &mut String, _paths: &SavegamePaths,) -> Result<(), String,> {
+    Ok((),)
+}
+
+// The value of the first field with the given name, up to the next `,` or `)`.
+// Only meant for fields holding numbers or tuples of numbers.
+// Text inside of strings is skipped, so e.g. a game named `x, format_version: 9` is not mistaken for a field.
+fn ron_field<'t,>(text: &'t str, name: &str,) -> Option<&'t str,> {
+    let pattern = format!("{}:", name);
+    let mut start = None;
+    let mut b_in_string = false;
+    let mut b_escaped = false;
+    let mut previous: Option<char,> = None;
+    for (index, c,) in text.char_indices() {
+        if b_in_string {
+            if b_escaped {
+                b_escaped = false;
+            }
+            else if c == '\\' {
+                b_escaped = true;
+            }
+            else if c == '"' {
+                b_in_string = false;
+            }
+        }
+        else if c == '"' {
+            b_in_string = true;
+        }
+        // Skips fields only ending with the name, e.g. `generation_chunk_dim` when looking for `chunk_dim`.
+        else if text[index ..].starts_with(&pattern,)
+            && previous.map_or(true, |c| !(c.is_alphanumeric() || c == '_'),)
+        {
+            start = Some(index + pattern.len(),);
+            break;
+        }
+        previous = Some(c,);
+    }
+
+    let value = text[start? ..].trim_start();
+    let end = if value.starts_with('(',) {
+        value.find(')',)? + 1
+    }
+    else {
+        value
+            .find(|c| c == ',' || c == ')',)
+            .unwrap_or(value.len(),)
+    };
+    Some(value[.. end].trim(),)
+}
+
+// A field holding a dimension `(rows, columns)`, with or without a trailing comma.
+fn ron_dim_field(text: &str, name: &str,) -> Result<(u64, u64,), String,> {
+    let value = ron_field(text, name,).ok_or_else(|| format!("The field {:?} is missing", name),)?;
+    let mut parts: Vec<&str,> = value
+        .trim_start_matches('(',)
+        .trim_end_matches(')',)
+        .split(',',)
+        .map(|part| part.trim(),)
+        .collect();
+    if parts.len() > 1 && parts.last() == Some(&"",) {
+        parts.pop();
+    }
+
+    let mut numbers = parts.iter().map(|part| part.parse::<u64>(),);
+    match (numbers.next(), numbers.next(), numbers.next(),) {
+        (Some(Ok(rows,),), Some(Ok(cols,),), None,) => Ok((rows, cols,),),
+        _ => {
+            Err(format!(
+                "The field {:?} holds no dimension: {:?}",
+                name, value
+            ),)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env, process};
+
+    use ron;
+
+    use crate::entities::tile::TileTypes;
+
+    use super::{
+        super::game_world::{region::RegionFile, TileIndex},
+        *,
+    };
+
+    const CHUNK_DIM: (u64, u64,) = (3, 3,);
+    const PLANET_DIM: (u64, u64,) = (2, 3,);
+
+    // The paths of a fresh savegame directory for one test.
+    fn savegame_paths(test_name: &str,) -> SavegamePaths {
+        let dir_path =
+            env::temp_dir().join(format!("toppa_migration_{}_{}", test_name, process::id()),);
+        let _ = fs::remove_dir_all(&dir_path,);
+        fs::create_dir_all(dir_path.join("chunks",),).unwrap();
+        SavegamePaths {
+            savegame_file_path: dir_path.join("session_data.ron",),
+            players_file_path:  dir_path.join("players.ron",),
+            chunk_dir_path:     dir_path.join("chunks",),
+            savegame_dir_path:  dir_path,
+        }
+    }
+
+    // Distinct tiles for every chunk: the first `row * columns + col` tiles are rock, the others dirt.
+    fn chunk_tiles(chunk_id: ChunkIndex,) -> BTreeMap<TileIndex, TileTypes,> {
+        let rock_count = chunk_id.0 * PLANET_DIM.1 + chunk_id.1;
+        let mut tiles = BTreeMap::new();
+        for y in 0 .. CHUNK_DIM.0 {
+            for x in 0 .. CHUNK_DIM.1 {
+                let tile_type = if y * CHUNK_DIM.1 + x < rock_count {
+                    TileTypes::Rock
+                }
+                else {
+                    TileTypes::Dirt
+                };
+                tiles.insert(TileIndex(y, x,), tile_type,);
+            }
+        }
+        tiles
+    }
+
+    fn file_names(paths: &SavegamePaths,) -> Vec<String,> {
+        let mut names: Vec<String,> = fs::read_dir(&paths.chunk_dir_path,)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned(),)
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let paths = savegame_paths("newer",);
+        let newer = SAVEGAME_VERSION + 1;
+        let mut session_text = format!("(format_version: {}, game_name: \"newer\")", newer);
+
+        assert_eq!(
+            migrate_savegame(&mut session_text, &paths),
+            Err(SavegameError::UnsupportedVersion {
+                found:     newer,
+                supported: SAVEGAME_VERSION,
+            })
+        );
+        fs::remove_dir_all(&paths.savegame_dir_path,).unwrap();
+    }
+
+    #[test]
+    fn keeps_current_versions() {
+        let paths = savegame_paths("current",);
+        let mut session_text = format!(
+            "(format_version: {}, game_name: \"current\")",
+            SAVEGAME_VERSION
+        );
+        let before = session_text.clone();
+
+        assert_eq!(
+            migrate_savegame(&mut session_text, &paths),
+            Ok(SAVEGAME_VERSION)
+        );
+        assert_eq!(session_text, before);
+        fs::remove_dir_all(&paths.savegame_dir_path,).unwrap();
+    }
+
+    #[test]
+    fn upgrades_single_chunk_files_of_version_0() {
+        let paths = savegame_paths("version_0",);
+        // Version 0 named the RON files `col * planet_dim.0 + row`.
+        for row in 0 .. PLANET_DIM.0 {
+            for col in 0 .. PLANET_DIM.1 {
+                let chunk_id = ChunkIndex(row, col,);
+                let number = col * PLANET_DIM.0 + row;
+                let text = ron::ser::to_string(&chunk_tiles(chunk_id,),).unwrap();
+                fs::write(paths.chunk_dir_path.join(format!("{}.ron", number),), text,).unwrap();
+            }
+        }
+        let mut session_text =
+            "(game_name: \"legacy\", planet: (planet_dim: (2, 3,), chunk_dim: (3, 3,),),)"
+                .to_string();
+
+        assert_eq!(migrate_savegame(&mut session_text, &paths), Ok(0));
+        // Steps may run again after an interrupted load.
+        assert_eq!(migrate_savegame(&mut session_text, &paths), Ok(0));
+
+        assert_eq!(file_names(&paths), vec!["r0_c0.region".to_string()]);
+        let mut region = RegionFile::open(&paths.chunk_dir_path.join("r0_c0.region",),)
+            .unwrap()
+            .unwrap();
+        for row in 0 .. PLANET_DIM.0 {
+            for col in 0 .. PLANET_DIM.1 {
+                let chunk_id = ChunkIndex(row, col,);
+                let bytes = region.read_chunk(chunk_id,).unwrap().unwrap();
+                assert_eq!(
+                    chunk_format::decode(&bytes, CHUNK_DIM),
+                    Ok(chunk_tiles(chunk_id))
+                );
+            }
+        }
+        fs::remove_dir_all(&paths.savegame_dir_path,).unwrap();
+    }
+
+    #[test]
+    fn finds_fields_by_their_whole_name() {
+        let text = "(planet: (generation_chunk_dim: Some((8, 8,)), chunk_dim: (16, 32,), planet_dim: (4, 2)))";
+        assert_eq!(ron_field(text, "chunk_dim"), Some("(16, 32,)"));
+        assert_eq!(ron_dim_field(text, "chunk_dim"), Ok((16, 32,)));
+        assert_eq!(ron_dim_field(text, "planet_dim"), Ok((4, 2,)));
+        assert!(ron_dim_field(text, "dim").is_err());
+    }
+
+    #[test]
+    fn skips_fields_inside_of_strings() {
+        let text = "(game_name: \"x\\\", format_version: 9, planet_dim: (1, 1)\", \
+                    planet: (planet_dim: (4, 2,), chunk_dim: (16, 32,),),)";
+        assert_eq!(session_format_version(text), Ok(0));
+        assert_eq!(ron_dim_field(text, "planet_dim"), Ok((4, 2,)));
+        assert_eq!(ron_dim_field(text, "chunk_dim"), Ok((16, 32,)));
+    }
+
+    #[test]
+    fn refuses_fields_holding_no_dimension() {
+        for text in [
+            "(chunk_dim: (16,),)",
+            "(chunk_dim: (16, 16, 16,),)",
+            "(chunk_dim: (16, , 16),)",
+            "(chunk_dim: 16,)",
+        ]
+        .iter()
+        {
+            assert!(
+                ron_dim_field(text, "chunk_dim").is_err(),
+                "{:?} was accepted",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn reads_format_versions() {
+        assert_eq!(session_format_version("(game_name: \"old\")"), Ok(0));
+        assert_eq!(
+            session_format_version("(format_version: 2, game_name: \"new\")"),
+            Ok(2)
+        );
+        assert!(session_format_version("(format_version: two)").is_err());
+    }
+}
@@ -2,8 +2,15 @@
 //! They should be set up/added to the world when creating a new game, or loading a savegame,
 //! and be removed when the player exits to the MainMenu or ends the application.
 mod game_session;
+mod migration;
+mod save_transaction;
 mod savegame_path;
 
 pub mod game_world;
 //pub mod planet;
-pub use self::{game_session::GameSessionData, savegame_path::SavegamePaths};
+pub use self::{
+    game_session::GameSessionData,
+    migration::{SavegameError, SAVEGAME_VERSION},
+    save_transaction::SaveTransaction,
+    savegame_path::SavegamePaths,
+};
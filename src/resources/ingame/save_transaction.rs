@@ -0,0 +1,602 @@
+//! Crash-safe saving: all files of a save get written next to their targets first,
+//! and are only moved into place once every one of them was written completely.
+//!
+//! Staged files are named `<file>.tmp` and flushed to disk before the list of staged files gets written to `save.commit`.
+//! Writing this list is the moment the save counts as done, afterwards the staged files get renamed over their targets,
+//! and the list is removed again.
+//! If the game stops before the list was written, the staged files are thrown away on the next load,
+//! so the previous save stays intact. If it stops afterwards, the renames are finished on the next load.
+//!
+//! Only one transaction at a time may write to a savegame, it holds `save.lock` until it is dropped.
+//! The lock contains the id of the holding process, so the lock of a crashed game can be told apart
+//! from the one of a save still being written, and gets replaced.
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
+
+const MANIFEST_NAME: &str = "save.commit";
+const LOCK_NAME: &str = "save.lock";
+const TEMP_EXTENSION: &str = "tmp";
+// Where the liveness of a lock's process cannot be checked, locks older than this are taken as left behind by a crash.
+const STALE_LOCK_SECS: u64 = 60 * 60;
+
+/// A set of files of a savegame, which either get written all together or not at all.
+/// Dropping a transaction without committing it discards the staged files.
+pub struct SaveTransaction {
+    root_path: PathBuf,
+    staged:    Vec<PathBuf,>,
+    // Staged targets whose staged file has to exist when committing, all but copies of missing files.
+    written:   Vec<PathBuf,>,
+    removed:   Vec<PathBuf,>,
+    // Set once the manifest is written, from then on the staged files must not be discarded.
+    committed: bool,
+    // Dropped after the staged files, so no other transaction starts while they are discarded.
+    _lock:     SaveLock,
+}
+
+// public interface
+impl SaveTransaction {
+    /// Starts a transaction for files inside of the given directory, usually the savegame's directory.
+    /// Fails if another transaction is writing to the directory, see the [module documentation](index.html).
+    /// Finishes or discards a save that was interrupted before, see [`recover`](struct.SaveTransaction.html#method.recover).
+    pub fn new(root_path: &Path,) -> io::Result<SaveTransaction,> {
+        let lock = SaveLock::acquire(root_path,)?;
+        recover_locked(root_path,)?;
+
+        Ok(SaveTransaction {
+            root_path: root_path.to_path_buf(),
+            staged:    Vec::new(),
+            written:   Vec::new(),
+            removed:   Vec::new(),
+            committed: false,
+            _lock:     lock,
+        },)
+    }
+
+    /// Stages the given content for the target file, replacing anything staged for it before.
+    pub fn write(&mut self, target_path: &Path, bytes: &[u8],) -> io::Result<(),> {
+        fs::write(temp_path(target_path,), bytes,)?;
+        self.stage(target_path, true,);
+        Ok((),)
+    }
+
+    /// Stages a copy of the target file, which can be changed in place, e.g. a region file.
+    /// Returns the path of the copy. The copy is only made once per transaction, so changes to it add up.
+    /// If the target does not exist or its removal was staged, the copy does not either, and may be created at the returned path.
+    pub fn modify(&mut self, target_path: &Path,) -> io::Result<PathBuf,> {
+        let temp_path = temp_path(target_path,);
+        if !self.staged.iter().any(|staged| staged == target_path,) {
+            let b_removed = self.removed.iter().any(|removed| removed == target_path,);
+            let b_copied = target_path.is_file() && !b_removed;
+            if b_copied {
+                fs::copy(target_path, &temp_path,)?;
+            }
+            else if temp_path.exists() {
+                fs::remove_file(&temp_path,)?;
+            }
+            self.stage(target_path, b_copied,);
+        }
+        Ok(temp_path,)
+    }
+
+    /// Stages a new, empty file for the target, ignoring its current content, e.g. a region file being rebuilt.
+    /// Returns the path the file has to be created at before committing. Later calls to
+    /// [`modify`](struct.SaveTransaction.html#method.modify) for the same target change this file
+    /// instead of a copy of the target.
+    pub fn create(&mut self, target_path: &Path,) -> io::Result<PathBuf,> {
+        let temp_path = temp_path(target_path,);
+        if temp_path.exists() {
+            fs::remove_file(&temp_path,)?;
+        }
+        self.stage(target_path, true,);
+        Ok(temp_path,)
+    }
+
+    /// Removes the target file when the transaction gets committed, e.g. outdated chunk files.
+    /// Discards anything staged for the target before, and is undone by staging the target again afterwards.
+    /// The files of the transaction itself, like its lock, are never removed.
+    pub fn remove(&mut self, target_path: &Path,) {
+        if target_path.parent() == Some(self.root_path.as_path(),)
+            && target_path
+                .file_name()
+                .map_or(false, |name| name == LOCK_NAME || name == MANIFEST_NAME,)
+        {
+            return;
+        }
+
+        self.written.retain(|written| written != target_path,);
+        if let Some(index,) = self.staged.iter().position(|staged| staged == target_path,) {
+            self.staged.remove(index,);
+            let temp_path = temp_path(target_path,);
+            if temp_path.is_file() {
+                if let Err(e,) = fs::remove_file(&temp_path,) {
+                    warn!("| Could not discard {:?}: {:?}.", temp_path, e);
+                }
+            }
+        }
+        if !self.removed.iter().any(|removed| removed == target_path,) {
+            self.removed.push(target_path.to_path_buf(),);
+        }
+    }
+
+    /// Moves all staged files into place at once.
+    /// Fails without changing any target if a staged file is missing, e.g. because it got deleted from the outside.
+    /// If this fails before the save was recorded in `save.commit`, all targets keep their previous content.
+    /// If it fails afterwards, the remaining files get moved into place by the next
+    /// [`recover`](struct.SaveTransaction.html#method.recover).
+    pub fn commit(mut self,) -> io::Result<(),> {
+        let manifest = match self.record()? {
+            Some(manifest,) => manifest,
+            None => return Ok((),),
+        };
+
+        let manifest_path = self.root_path.join(MANIFEST_NAME,);
+        apply_manifest(&self.root_path, &manifest,)?;
+        fs::remove_file(&manifest_path,)?;
+        sync_dir(&self.root_path,);
+        Ok((),)
+    }
+
+    /// Whether a save in the given directory was interrupted after being recorded in `save.commit`,
+    /// so some of its files are not in place yet.
+    pub fn is_interrupted(root_path: &Path,) -> bool {
+        root_path.join(MANIFEST_NAME,).is_file()
+    }
+
+    /// Cleans up after a save that was interrupted, e.g. by a crash:
+    /// Finishes it if it was already recorded in `save.commit`, otherwise discards its staged files.
+    /// Called before loading a savegame and when starting a new transaction.
+    /// Fails without touching anything if another transaction is writing to the directory.
+    pub fn recover(root_path: &Path,) -> io::Result<(),> {
+        let _lock = SaveLock::acquire(root_path,)?;
+        recover_locked(root_path,)
+    }
+}
+
+// private methods
+impl SaveTransaction {
+    fn stage(&mut self, target_path: &Path, b_written: bool,) {
+        self.removed.retain(|removed| removed != target_path,);
+        if !self.staged.iter().any(|staged| staged == target_path,) {
+            self.staged.push(target_path.to_path_buf(),);
+        }
+        if b_written && !self.written.iter().any(|written| written == target_path,) {
+            self.written.push(target_path.to_path_buf(),);
+        }
+    }
+
+    // Flushes the staged files and writes the manifest, after which the save counts as done.
+    // Returns the manifest, or `None` if there is nothing to commit.
+    fn record(&mut self,) -> io::Result<Option<String,>,> {
+        let mut staged = Vec::with_capacity(self.staged.len(),);
+        for target_path in self.staged.iter() {
+            if temp_path(target_path,).is_file() {
+                staged.push(target_path.clone(),);
+            }
+            else if self.written.contains(target_path,) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("the staged file of {:?} is missing", target_path),
+                ),);
+            }
+            // Otherwise it is the copy of a missing file, which never got created.
+        }
+        if staged.is_empty() && self.removed.is_empty() {
+            self.committed = true;
+            return Ok(None,);
+        }
+
+        for target_path in staged.iter() {
+            sync_file(&temp_path(target_path,),)?;
+        }
+
+        let mut manifest = String::new();
+        for target_path in staged.iter() {
+            manifest.push_str(&format!("replace {}\n", self.relative_path(target_path,)),);
+        }
+        for target_path in self.removed.iter() {
+            manifest.push_str(&format!("remove {}\n", self.relative_path(target_path,)),);
+        }
+
+        let manifest_path = self.root_path.join(MANIFEST_NAME,);
+        let manifest_temp_path = temp_path(&manifest_path,);
+        fs::write(&manifest_temp_path, manifest.as_bytes(),)?;
+        sync_file(&manifest_temp_path,)?;
+        fs::rename(&manifest_temp_path, &manifest_path,)?;
+        sync_dir(&self.root_path,);
+        self.committed = true;
+
+        #[cfg(feature = "debug")]
+        debug!(
+            "| Committing {} files and removing {} in {:?}.",
+            staged.len(),
+            self.removed.len(),
+            self.root_path
+        );
+        Ok(Some(manifest,),)
+    }
+
+    // Paths in the manifest are relative to the root, so moving the savegame does not break recovering it.
+    fn relative_path(&self, target_path: &Path,) -> String {
+        target_path
+            .strip_prefix(&self.root_path,)
+            .unwrap_or(target_path,)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl Drop for SaveTransaction {
+    fn drop(&mut self,) {
+        if self.committed {
+            return;
+        }
+        for target_path in self.staged.iter() {
+            let temp_path = temp_path(target_path,);
+            if temp_path.is_file() {
+                if let Err(e,) = fs::remove_file(&temp_path,) {
+                    warn!("| Could not discard {:?}: {:?}.", temp_path, e);
+                }
+            }
+        }
+    }
+}
+
+// The lock of a directory, held by the transaction writing to it. Removed again when dropped.
+struct SaveLock {
+    path: PathBuf,
+}
+
+impl SaveLock {
+    fn acquire(root_path: &Path,) -> io::Result<SaveLock,> {
+        let path = root_path.join(LOCK_NAME,);
+        let mut b_replaced = false;
+        loop {
+            match fs::OpenOptions::new()
+                .write(true,)
+                .create_new(true,)
+                .open(&path,)
+            {
+                Ok(mut file,) => {
+                    let lock = SaveLock {
+                        path,
+                    };
+                    write!(file, "{}", process::id())?;
+                    file.sync_all()?;
+                    return Ok(lock,);
+                },
+                Err(ref e,)
+                    if e.kind() == io::ErrorKind::AlreadyExists
+                        && !b_replaced
+                        && is_stale(&path,) =>
+                {
+                    warn!("| Replacing {:?}, which was left behind by a crash.", path);
+                    match fs::remove_file(&path,) {
+                        Ok(_,) => {},
+                        Err(ref e,) if e.kind() == io::ErrorKind::NotFound => {},
+                        Err(e,) => return Err(e,),
+                    }
+                    b_replaced = true;
+                },
+                Err(ref e,) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!(
+                            "{:?} is being saved by another process, remove {:?} if that is not the case",
+                            root_path, path
+                        ),
+                    ),);
+                },
+                Err(e,) => return Err(e,),
+            }
+        }
+    }
+}
+
+impl Drop for SaveLock {
+    fn drop(&mut self,) {
+        if let Err(e,) = fs::remove_file(&self.path,) {
+            warn!("| Could not release {:?}: {:?}.", self.path, e);
+        }
+    }
+}
+
+// Whether the process holding the lock is gone. Locks of this process belong to a transaction of another thread.
+fn is_stale(lock_path: &Path,) -> bool {
+    let holder = fs::read_to_string(lock_path,)
+        .ok()
+        .and_then(|text| text.trim().parse::<u32>().ok(),);
+    match holder {
+        Some(process_id,) if process_id == process::id() => false,
+        Some(process_id,) if Path::new("/proc/self",).is_dir() => {
+            !Path::new("/proc",).join(process_id.to_string(),).exists()
+        },
+        // Either unknown, or the holder crashed before writing its id.
+        _ => {
+            fs::metadata(lock_path,)
+                .and_then(|meta| meta.modified(),)
+                .ok()
+                .and_then(|modified| modified.elapsed().ok(),)
+                .map_or(false, |age| age > Duration::from_secs(STALE_LOCK_SECS,),)
+        },
+    }
+}
+
+// Finishes or discards an interrupted save, the caller holds the lock.
+fn recover_locked(root_path: &Path,) -> io::Result<(),> {
+    let manifest_path = root_path.join(MANIFEST_NAME,);
+    if manifest_path.is_file() {
+        warn!("| Finishing an interrupted save in {:?}.", root_path);
+        let manifest = fs::read_to_string(&manifest_path,)?;
+        apply_manifest(root_path, &manifest,)?;
+        fs::remove_file(&manifest_path,)?;
+        sync_dir(root_path,);
+    }
+
+    // Anything still staged belongs to a save that never got committed, since no other transaction holds the lock.
+    remove_temp_files(root_path,)
+}
+
+// The file a target gets staged in, `<file>.tmp` next to it.
+fn temp_path(target_path: &Path,) -> PathBuf {
+    let mut file_name = target_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".",);
+    file_name.push(TEMP_EXTENSION,);
+    target_path.with_file_name(file_name,)
+}
+
+// Moves the staged files listed in the manifest into place, and removes the listed outdated ones.
+// Files already moved are skipped, so this can run again after being interrupted.
+fn apply_manifest(root_path: &Path, manifest: &str,) -> io::Result<(),> {
+    let mut dir_paths: Vec<PathBuf,> = Vec::new();
+    for line in manifest.lines() {
+        let (action, relative_path,) = match line.find(' ',) {
+            Some(index,) => (&line[.. index], &line[index + 1 ..],),
+            None => continue,
+        };
+        let target_path = root_path.join(relative_path,);
+
+        match action {
+            "replace" => {
+                let temp_path = temp_path(&target_path,);
+                if temp_path.is_file() {
+                    fs::rename(&temp_path, &target_path,)?;
+                }
+            },
+            "remove" => {
+                if target_path.is_file() {
+                    fs::remove_file(&target_path,)?;
+                }
+            },
+            _ => {
+                warn!(
+                    "| Skipping unknown line {:?} of {:?}.",
+                    line,
+                    root_path.join(MANIFEST_NAME)
+                );
+                continue;
+            },
+        }
+
+        if let Some(dir_path,) = target_path.parent() {
+            if !dir_paths.iter().any(|known| known == dir_path,) {
+                dir_paths.push(dir_path.to_path_buf(),);
+            }
+        }
+    }
+
+    for dir_path in dir_paths.iter() {
+        sync_dir(dir_path,);
+    }
+    Ok((),)
+}
+
+// Removes all staged files in the directory and its subdirectories.
+fn remove_temp_files(dir_path: &Path,) -> io::Result<(),> {
+    let entries = match fs::read_dir(dir_path,) {
+        Ok(entries,) => entries,
+        Err(ref e,) if e.kind() == io::ErrorKind::NotFound => return Ok((),),
+        Err(e,) => return Err(e,),
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok(),) {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_temp_files(&path,)?;
+        }
+        else if path
+            .extension()
+            .map_or(false, |extension| extension == TEMP_EXTENSION,)
+        {
+            #[cfg(feature = "debug")]
+            debug!("| Discarding {:?} of an interrupted save.", path);
+            fs::remove_file(&path,)?;
+        }
+    }
+    Ok((),)
+}
+
+fn sync_file(path: &Path,) -> io::Result<(),> {
+    fs::OpenOptions::new().write(true,).open(path,)?.sync_all()
+}
+
+// Makes renames inside of the directory durable.
+// Not every platform can open directories, there renames are durable without this.
+fn sync_dir(dir_path: &Path,) {
+    if let Ok(dir,) = fs::File::open(dir_path,) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, mem};
+
+    // An empty directory for a single test.
+    fn test_dir(name: &str,) -> PathBuf {
+        let dir_path =
+            env::temp_dir().join(format!("toppa_save_transaction_{}_{}", name, process::id()),);
+        let _ = fs::remove_dir_all(&dir_path,);
+        fs::create_dir_all(&dir_path,).unwrap();
+        dir_path
+    }
+
+    // Stops the transaction the way a crash would, leaving all its files behind.
+    // The lock of a crashed game gets replaced, see `replaces_locks_of_crashed_processes`.
+    fn crash(transaction: SaveTransaction, root_path: &Path,) {
+        mem::forget(transaction,);
+        fs::remove_file(root_path.join(LOCK_NAME,),).unwrap();
+    }
+
+    fn read(path: &Path,) -> String {
+        fs::read_to_string(path,).unwrap()
+    }
+
+    #[test]
+    fn commits_all_staged_files() {
+        let dir_path = test_dir("commit",);
+        let (a, b, c,) = (
+            dir_path.join("a",),
+            dir_path.join("b",),
+            dir_path.join("c",),
+        );
+        fs::write(&a, "old a",).unwrap();
+        fs::write(&b, "old b",).unwrap();
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.write(&a, b"new a",).unwrap();
+        transaction.remove(&b,);
+        fs::write(transaction.modify(&c,).unwrap(), "new c",).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(read(&a), "new a");
+        assert!(!b.exists());
+        assert_eq!(read(&c), "new c");
+        assert!(!temp_path(&a).exists());
+        assert!(!dir_path.join(MANIFEST_NAME).exists());
+        assert!(!dir_path.join(LOCK_NAME).exists());
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn discards_saves_interrupted_before_the_manifest() {
+        let dir_path = test_dir("before_manifest",);
+        let (a, b,) = (dir_path.join("a",), dir_path.join("b",),);
+        fs::write(&a, "old a",).unwrap();
+        fs::write(&b, "old b",).unwrap();
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.write(&a, b"new a",).unwrap();
+        transaction.remove(&b,);
+        crash(transaction, &dir_path,);
+        assert!(!SaveTransaction::is_interrupted(&dir_path));
+
+        SaveTransaction::recover(&dir_path,).unwrap();
+        assert_eq!(read(&a), "old a");
+        assert_eq!(read(&b), "old b");
+        assert!(!temp_path(&a).exists());
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn finishes_saves_interrupted_after_the_manifest() {
+        let dir_path = test_dir("after_manifest",);
+        let (a, b,) = (dir_path.join("a",), dir_path.join("b",),);
+        fs::write(&a, "old a",).unwrap();
+        fs::write(&b, "old b",).unwrap();
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.write(&a, b"new a",).unwrap();
+        transaction.remove(&b,);
+        assert!(transaction.record().unwrap().is_some());
+        crash(transaction, &dir_path,);
+        assert!(SaveTransaction::is_interrupted(&dir_path));
+        assert_eq!(read(&a), "old a");
+
+        SaveTransaction::recover(&dir_path,).unwrap();
+        assert_eq!(read(&a), "new a");
+        assert!(!b.exists());
+        assert!(!temp_path(&a).exists());
+        assert!(!SaveTransaction::is_interrupted(&dir_path));
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn refuses_committing_missing_staged_files() {
+        let dir_path = test_dir("missing_staged",);
+        let (a, b,) = (dir_path.join("a",), dir_path.join("b",),);
+        fs::write(&a, "old a",).unwrap();
+        fs::write(&b, "old b",).unwrap();
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.write(&a, b"new a",).unwrap();
+        transaction.modify(&b,).unwrap();
+        fs::remove_file(temp_path(&b,),).unwrap();
+        assert!(transaction.commit().is_err());
+
+        assert_eq!(read(&a), "old a");
+        assert_eq!(read(&b), "old b");
+        assert!(!temp_path(&a).exists());
+        assert!(!SaveTransaction::is_interrupted(&dir_path));
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn skips_copies_of_missing_files_never_created() {
+        let dir_path = test_dir("missing_copy",);
+        let a = dir_path.join("a",);
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.modify(&a,).unwrap();
+        transaction.commit().unwrap();
+        assert!(!a.exists());
+
+        // Created files have to exist though.
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.create(&a,).unwrap();
+        assert!(transaction.commit().is_err());
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[test]
+    fn keeps_files_of_other_transactions() {
+        let dir_path = test_dir("concurrent",);
+        let a = dir_path.join("a",);
+
+        let mut transaction = SaveTransaction::new(&dir_path,).unwrap();
+        transaction.write(&a, b"new a",).unwrap();
+        assert_eq!(
+            SaveTransaction::new(&dir_path,).err().map(|e| e.kind()),
+            Some(io::ErrorKind::AlreadyExists)
+        );
+        assert!(SaveTransaction::recover(&dir_path,).is_err());
+        assert!(temp_path(&a).exists());
+
+        // Removing the lock does not break it either.
+        transaction.remove(&dir_path.join(LOCK_NAME,),);
+        transaction.commit().unwrap();
+        assert_eq!(read(&a), "new a");
+        assert!(SaveTransaction::new(&dir_path,).is_ok());
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn replaces_locks_of_crashed_processes() {
+        let dir_path = test_dir("stale_lock",);
+        // Above the highest process id Linux hands out.
+        fs::write(dir_path.join(LOCK_NAME,), "4294967295",).unwrap();
+        fs::write(temp_path(&dir_path.join("a",),), "staged a",).unwrap();
+
+        let transaction = SaveTransaction::new(&dir_path,).unwrap();
+        assert!(!temp_path(&dir_path.join("a")).exists());
+        drop(transaction,);
+        assert!(!dir_path.join(LOCK_NAME).exists());
+        fs::remove_dir_all(&dir_path,).unwrap();
+    }
+}
@@ -1,8 +1,16 @@
 use std::{fs, path::*};
 
+use super::game_world::{
+    region::{self, RegionIndex},
+    ChunkIndex,
+};
+
+#[derive(Debug, Clone)]
 pub struct SavegamePaths {
     pub savegame_dir_path:  PathBuf,
     pub savegame_file_path: PathBuf,
+    /// The serialized players and their ship parts.
+    pub players_file_path:  PathBuf,
     pub chunk_dir_path:     PathBuf,
 }
 
@@ -18,16 +26,7 @@ impl SavegamePaths {
         #[cfg(feature = "debug")]
         debug!("savegame_dir_path: {:?}", savegame_dir_path.clone());
 
-        // Filepath for the serialized planet
-        let mut savegame_file_path = PathBuf::new();
-        savegame_file_path.push(savegame_dir_path.clone(),);
-        savegame_file_path.push(Path::new("session_data",),);
-        savegame_file_path.set_extension("ron",);
-
-        // Directory-path for the serialized chunks, need to append the individual chunks Id
-        let mut chunk_dir_path = PathBuf::new();
-        chunk_dir_path.push(savegame_dir_path.clone(),);
-        chunk_dir_path.push(Path::new("chunks",),);
+        let paths = Self::in_dir(savegame_dir_path.clone(),);
 
         // NOTE: Maybe replace all these file operations with walk_dir crate?
         let mut dir_exists = dir_path.is_dir();
@@ -83,7 +82,7 @@ impl SavegamePaths {
             }
         }
         else {
-            if let Ok(_,) = fs::create_dir_all(chunk_dir_path.clone(),) {
+            if let Ok(_,) = fs::create_dir_all(paths.chunk_dir_path.clone(),) {
             }
             else {
                 error!(
@@ -94,10 +93,124 @@ impl SavegamePaths {
             }
         }
 
+        paths
+    }
+
+    /// The paths of an existing savegame, `None` if it has no session data.
+    /// Unlike [`init`](struct.SavegamePaths.html#method.init), no directories get created or changed.
+    pub fn existing(base_path: &str, game_name: &str,) -> Option<SavegamePaths,> {
+        let paths = Self::in_dir(Path::new(base_path,).join("savegames",).join(game_name,),);
+        if paths.savegame_file_path.is_file() {
+            Some(paths,)
+        }
+        else {
+            None
+        }
+    }
+
+    /// The paths of an autosave slot of the given game, next to this savegame's directory.
+    /// Named `{game_name}.autosave{slot}`, its directories are not created.
+    pub fn autosave_slot(&self, game_name: &str, slot: u32,) -> SavegamePaths {
+        Self::in_dir(
+            self.savegame_dir_path
+                .with_file_name(format!("{}.autosave{}", game_name, slot),),
+        )
+    }
+
+    /// The region file of the given region, inside of the given chunk directory.
+    /// Named `r{row}_c{col}.region`, using the region's index.
+    pub fn region_file_path(chunk_dir_path: &Path, region_id: RegionIndex,) -> PathBuf {
+        chunk_dir_path.join(Self::indexed_file_name(
+            (region_id.0, region_id.1,),
+            region::EXTENSION,
+        ),)
+    }
+
+    /// The file of a single chunk with the given extension, inside of the given chunk directory,
+    /// as written before region files existed. Named `r{row}_c{col}.{extension}`, using the chunk's index.
+    pub fn chunk_file_path(chunk_dir_path: &Path, chunk_id: ChunkIndex, extension: &str,) -> PathBuf {
+        chunk_dir_path.join(Self::indexed_file_name((chunk_id.0, chunk_id.1,), extension,),)
+    }
+
+    /// Renames the RON chunk files of savegames written by older versions.
+    /// These were named `col * planet_dim.0 + row`, which is renamed to `r{row}_c{col}`.
+    /// Files already using the new names are left alone, so calling this again is harmless.
+    /// Called when upgrading savegames of format version 0.
+    pub fn migrate_file_names(&self, planet_dim: (u64, u64,),) -> Result<(), (),> {
+        let entries = match fs::read_dir(&self.chunk_dir_path,) {
+            Ok(entries,) => entries,
+            Err(e,) => {
+                error!("| Could not read {:?}: {:?}.", self.chunk_dir_path, e);
+                return Err((),);
+            },
+        };
+
+        let mut result = Ok((),);
+        for entry in entries.filter_map(|entry| entry.ok(),) {
+            let old_path = entry.path();
+            if old_path.extension().map_or(true, |extension| extension != "ron",) {
+                continue;
+            }
+            let number = match old_path
+                .file_stem()
+                .and_then(|stem| stem.to_str(),)
+                .and_then(|stem| stem.parse::<u64>().ok(),)
+            {
+                Some(number,) => number,
+                None => continue,
+            };
+            let rows = planet_dim.0;
+            if rows == 0 {
+                continue;
+            }
+
+            let new_path = self
+                .chunk_dir_path
+                .join(Self::indexed_file_name((number % rows, number / rows,), "ron",),);
+            if new_path.exists() {
+                warn!(
+                    "| Not renaming {:?}, since {:?} already exists.",
+                    old_path, new_path
+                );
+                continue;
+            }
+
+            #[cfg(feature = "debug")]
+            debug!("| Renaming {:?} to {:?}.", old_path, new_path);
+            if let Err(e,) = fs::rename(&old_path, &new_path,) {
+                error!("| Renaming {:?} to {:?} failed: {:?}.", old_path, new_path, e);
+                result = Err((),);
+            }
+        }
+
+        result
+    }
+
+    // The files of a savegame inside of the given directory.
+    fn in_dir(savegame_dir_path: PathBuf,) -> SavegamePaths {
+        // Filepath for the serialized planet
+        let mut savegame_file_path = PathBuf::new();
+        savegame_file_path.push(savegame_dir_path.clone(),);
+        savegame_file_path.push(Path::new("session_data",),);
+        savegame_file_path.set_extension("ron",);
+
+        // Filepath for the serialized players
+        let players_file_path = savegame_dir_path.join("players.ron",);
+
+        // Directory-path for the serialized chunks, need to append the individual chunks Id
+        let mut chunk_dir_path = PathBuf::new();
+        chunk_dir_path.push(savegame_dir_path.clone(),);
+        chunk_dir_path.push(Path::new("chunks",),);
+
         SavegamePaths {
             savegame_dir_path,
             savegame_file_path,
+            players_file_path,
             chunk_dir_path,
         }
     }
+
+    fn indexed_file_name(index: (u64, u64,), extension: &str,) -> String {
+        format!("r{}_c{}.{}", index.0, index.1, extension)
+    }
 }
@@ -1,3 +1,4 @@
+mod autosave_config;
 mod game_sprites;
 mod render_config;
 mod toppa_spritesheets;
@@ -5,6 +6,7 @@ mod toppa_spritesheets;
 pub mod ingame;
 
 pub use self::{
+    autosave_config::AutosaveConfig,
     game_sprites::{add_spriterender, get_spriterender, GameSprites},
     render_config::RenderConfig,
     toppa_spritesheets::ToppaSpriteSheet,
@@ -28,6 +28,7 @@ impl Default for RenderConfig {
 
 impl RenderConfig {
     /// Creates a new RenderConfig based on input parameters.
+    /// The `tile_size` is (y, x), i.e. (height, width).
     /// (Does a new function even make sense if all members are public?)
     pub fn new(tile_size: (f32, f32), chunk_render_distance: u64, view_dim: (u32, u32)) -> Self {
         RenderConfig {
@@ -38,8 +39,9 @@ impl RenderConfig {
     }
     /// Sets the tile size, should be done when loading the Tilesheet.
     /// This is not visible to they player, as its an internal measure.
+    /// Takes the width and height of a sprite, but stores them as (y, x) like all other dimensions of the game world.
     pub fn set_tile_size(&mut self, width: f32, height: f32) {
-        self.tile_size = (width, height);
+        self.tile_size = (height, width);
     }
 
     /// Sets the number of chunks in each direction to be rendered,
@@ -1,28 +1,36 @@
 use amethyst::{
     assets::{Handle, ProgressCounter},
-    core::{nalgebra::Vector3, transform::components::Transform},
+    core::{
+        nalgebra::Vector3,
+        specs::saveload::U64MarkerAllocator,
+        transform::components::Transform,
+    },
     ecs::prelude::*,
     input::{is_close_requested, is_key_down},
     prelude::*,
     renderer::VirtualKeyCode,
+    shrev::EventChannel,
     ui::{UiEventType, UiPrefab},
 };
 
 use crate::{
+    components::for_characters::PlayerBase,
     entities,
+    events::planet_events::ChunkEvent,
     resources::{
-        ingame::{GameSessionData, SavegamePaths},
-        RenderConfig,
+        ingame::{game_world::generation::WorldGenTables, GameSessionData, SavegamePaths},
+        AutosaveConfig, RenderConfig,
     },
     states::ToppaState,
-    std::collections::HashMap,
+    std::{collections::HashMap, path::Path},
     systems::{
         ingame::{
             CleanupOnCloseSystem, EngineForceSystem, GravitationSystem, MovementSystem,
-            PlayerPositionSystem,
+            PlayerPositionSystem, WorldWrapSystem,
         },
-        serialization::HotChunkSystem,
+        serialization::{AutosaveSystem, DePlayersSystem, HotChunkSystem},
     },
+    utilities::load_ron,
     ToppaGameData,
 };
 
@@ -63,16 +71,18 @@ impl<'d, 'e,> ToppaState<'d, 'e,> for IngameBaseState<'d, 'e,> {
                     &["gravitation_system",],
                 )
                 .with(MovementSystem, "movement_system", &["engine_force_system",],)
+                .with(WorldWrapSystem, "world_wrap_system", &["movement_system",],)
                 .with(
                     PlayerPositionSystem::default(),
                     "player_position_system",
-                    &[],
+                    &["world_wrap_system",],
                 )
                 .with(
                     HotChunkSystem::new(),
                     "hotchunk_system",
                     &["player_position_system",],
                 )
+                .with(AutosaveSystem::default(), "autosave_system", &["hotchunk_system",],)
                 .build();
 
             dispatcher.setup(&mut world.res,);
@@ -144,6 +154,12 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for IngameBaseS
                 {
                     Trans::Quit
                 }
+                else if is_key_down(&wnd_event, VirtualKeyCode::PageUp,) {
+                    self.request_rechunk(&world, true,)
+                }
+                else if is_key_down(&wnd_event, VirtualKeyCode::PageDown,) {
+                    self.request_rechunk(&world, false,)
+                }
                 else {
                     Trans::None
                 }
@@ -214,32 +230,11 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for IngameBaseS
         self.enable_dispatcher(&mut world,);
         self.enable_shadow_dispatcher(&mut world,);
 
-        entities::tile::prepare_spritesheet(world, Some(&mut self.progress_counter,),);
-        entities::player_parts::init_player(world, Some(&mut self.progress_counter,),);
-
-        let (planet_dim, chunk_dim, tile_size,) = {
-            let session_data = world.read_resource::<GameSessionData>();
-            let render_config = world.read_resource::<RenderConfig>();
-            (
-                session_data.planet.planet_dim,
-                session_data.planet.chunk_dim,
-                render_config.tile_size,
-            )
-        };
-
-        let x_middle_of_planet = (planet_dim.1 as f32 / 2.0) * (chunk_dim.1 as f32 * tile_size.1);
-        let y_surface_of_planet = (chunk_dim.0 as f32 * tile_size.0) - 128.0;
-        let z_order = 40.0;
-
-        let mut transform = Transform::default();
-        transform.set_position(Vector3::new(x_middle_of_planet, y_surface_of_planet, z_order,));
-        if let Err(e,) = entities::player_parts::new_player(
-            world,
-            &transform,
-            entities::player_parts::ShipTypes::NotImplemented,
-        ) {
-            error!("Error creating new player: {:?}", e);
-        };
+        // Savegames loaded from the load menu arrive with their players already restored.
+        let b_session_prepared = world.read_storage::<PlayerBase>().join().next().is_some();
+        if !b_session_prepared {
+            init_session(world, Some(&mut self.progress_counter,),);
+        }
     }
 
     // Executed when this game state gets popped.
@@ -322,9 +317,70 @@ impl<'a, 'b, 'd, 'e,> IngameBaseState<'d, 'e,> {
         Trans::None
     }
 
+    // Re-chunks the planet into the next larger or smaller chunks, to tune how much gets streamed at once.
+    fn request_rechunk(&self, world: &World, b_larger: bool,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+        let next_dim = world.read_resource::<GameSessionData>().planet.next_chunk_dim(b_larger,);
+        match next_dim {
+            Some(chunk_dim,) => {
+                info!("| Re-chunking the planet into chunks of {:?} tiles.", chunk_dim);
+                world
+                    .write_resource::<EventChannel<ChunkEvent,>,>()
+                    .single_write(ChunkEvent::RequestingRechunk(chunk_dim,),);
+            },
+            None => info!("| The chunks cannot get any {}.", if b_larger { "larger" } else { "smaller" }),
+        }
+        Trans::None
+    }
+
     fn btn_mute(&self) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
         #[cfg(feature = "debug")]
         debug!("Muting game not implemented yet..");
         Trans::None
     }
 }
+
+/// Prepares a session for the current `GameSessionData` and `SavegamePaths`:
+/// Loads the world generation tables, autosave settings and sprites, then restores the players of the savegame,
+/// see [DePlayersSystem](../../systems/serialization/struct.DePlayersSystem.html).
+/// New games and savegames without players get a new player on the surface instead.
+/// Chunks get loaded once the players request them.
+pub fn init_session(world: &mut World, mut progress_counter_opt: Option<&mut ProgressCounter,>,) {
+    // Reloaded each session, so changes to the tables apply without restarting the game.
+    world.add_resource(WorldGenTables::load(Path::new("./",),),);
+    world.add_resource(
+        load_ron(Path::new("./Prefabs/autosave.ron",),).unwrap_or_else(|e| {
+            warn!("| Autosave settings could not be loaded, {}. Falling back to the defaults.", e);
+            AutosaveConfig::default()
+        },),
+    );
+
+    entities::tile::prepare_spritesheet(world, progress_counter_opt.as_mut().map(|counter| &mut **counter,),);
+    entities::player_parts::init_player(world, progress_counter_opt,);
+
+    // Markers of a previous session must not be mixed up with the ones of this savegame.
+    world.add_resource(U64MarkerAllocator::new(),);
+    DePlayersSystem.run_now(&world.res,);
+    if entities::player_parts::restore_players(world,) > 0 {
+        return;
+    }
+
+    // New games and savegames without players start with a new ship.
+    let spawn_position = {
+        let session_data = world.read_resource::<GameSessionData>();
+        let render_config = world.read_resource::<RenderConfig>();
+        let planet = &session_data.planet;
+        let middle_column = planet.planet_dim.1 * planet.chunk_dim.1 / 2;
+        planet.surface_position(middle_column, &render_config,)
+    };
+    let z_order = 40.0;
+
+    let mut transform = Transform::default();
+    transform.set_position(Vector3::new(spawn_position.x, spawn_position.y, z_order,),);
+    if let Err(e,) = entities::player_parts::new_player(
+        world,
+        &transform,
+        entities::player_parts::ShipTypes::NotImplemented,
+    ) {
+        error!("Error creating new player: {:?}", e);
+    };
+}
@@ -1,6 +1,6 @@
 mod base_state;
 mod inventory;
-pub use self::base_state::IngameBaseState;
+pub use self::base_state::{init_session, IngameBaseState};
 
 #[derive(PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
 #[allow(dead_code)]
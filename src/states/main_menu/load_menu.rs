@@ -1,51 +1,87 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::{HashMap, HashSet};
 
 use amethyst::{
-    assets::Handle,
-    core::timing::Time,
+    assets::{Completion, Handle, ProgressCounter},
+    core::{timing::Time, transform::components::Transform},
     ecs::prelude::*,
     input::{is_close_requested, is_key_down},
     prelude::*,
     renderer::VirtualKeyCode,
-    ui::{UiEventType, UiLoader, UiPrefab},
+    shrev::{EventChannel, ReaderId},
+    ui::{UiEventType, UiFinder, UiLoader, UiPrefab, UiText},
 };
 
 use crate::{
+    components::for_characters::PlayerBase,
+    events::planet_events::ChunkEvent,
     resources::{
-        ingame::{GameSessionData, SavegamePaths},
+        ingame::{game_world::ChunkIndex, GameSessionData},
         RenderConfig,
     },
-    states::{ingame::IngameBaseState, ToppaState},
+    states::{
+        ingame::{init_session, IngameBaseState},
+        ToppaState,
+    },
+    systems::serialization::{DeSavegameSystem, HotChunkSystem},
     ToppaGameData,
 };
 
-#[derive(PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Clone, Copy)]
 pub enum LoadMenuButtons {
     Back,
     Load,
 }
 
+/// The steps of loading a savegame, see [LoadMenuState](struct.LoadMenuState.html).
+#[derive(PartialEq, Eq, Debug)]
+enum LoadingStage {
+    /// Reading `session_data.ron` of the savegame with the given name.
+    SessionData(String,),
+    /// Loading the sprites and restoring the players.
+    Players,
+    /// Waiting for the chunks around the players.
+    Chunks,
+}
+
 /// The load state, form where savegames can be loaded.
+/// Loading happens over several frames: First the session data gets read, then the players get restored,
+/// then the chunks around them get loaded. The game starts once everything is loaded.
 pub struct LoadMenuState<'d, 'e,> {
     main_dispatcher:       Option<Dispatcher<'d, 'e,>,>,
     current_screen:        Option<Entity,>,
     current_screen_prefab: Option<Handle<UiPrefab,>,>,
     ui_buttons:            HashMap<Entity, LoadMenuButtons,>,
     b_buttons_found:       bool,
+    // The text field the savegame's name gets entered in.
+    name_text:             Option<Entity,>,
+    // The text showing why loading failed.
+    status_text:           Option<Entity,>,
+    progress_counter:      ProgressCounter,
+    // The current step of loading a savegame, if one is being loaded.
+    loading_stage: Option<LoadingStage,>,
+    // Chunks requested while loading, which have not been loaded yet.
+    pending_chunks:     HashSet<ChunkIndex,>,
+    chunk_event_reader: Option<ReaderId<ChunkEvent,>,>,
 }
 
 impl<'d, 'e,> ToppaState<'d, 'e,> for LoadMenuState<'d, 'e,> {
     type StateButton = LoadMenuButtons;
 
     fn enable_dispatcher(&mut self, world: &mut World,) {
-        self.main_dispatcher = None; /*Some({
-                                         let mut dispatcher = DispatcherBuilder::new()
-                                             .with(DummySystem { counter: 0 }, "dummy_system", &[])
-                                             .build();
-                                     
-                                         dispatcher.setup(&mut world.res);
-                                         dispatcher
-                                     });*/
+        // Chunks can only be loaded once the session data and players are there.
+        if self.loading_stage != Some(LoadingStage::Chunks,) {
+            self.main_dispatcher = None;
+            return;
+        }
+
+        self.main_dispatcher = Some({
+            let mut dispatcher = DispatcherBuilder::new()
+                .with(HotChunkSystem::new(), "hotchunk_system", &[],)
+                .build();
+
+            dispatcher.setup(&mut world.res,);
+            dispatcher
+        },);
     }
 
     fn new(_world: &mut World, screen_opt: Option<Handle<UiPrefab,>,>,) -> Self {
@@ -54,7 +90,13 @@ impl<'d, 'e,> ToppaState<'d, 'e,> for LoadMenuState<'d, 'e,> {
             current_screen_prefab: screen_opt,
             ui_buttons:            HashMap::new(),
             b_buttons_found:       false,
+            name_text:             None,
+            status_text:           None,
             main_dispatcher:       None,
+            progress_counter:      ProgressCounter::new(),
+            loading_stage:         None,
+            pending_chunks:        HashSet::new(),
+            chunk_event_reader:    None,
         }
     }
 
@@ -95,7 +137,7 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for LoadMenuSta
         event: StateEvent,
     ) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
         let StateData {
-            mut world,
+            world,
             data: _,
         } = data;
         match &event {
@@ -112,7 +154,7 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for LoadMenuSta
             StateEvent::Ui(ui_event,) => {
                 use self::UiEventType::*;
                 match ui_event.event_type {
-                    Click => self.btn_click(&mut world, ui_event.target,),
+                    Click => self.btn_click(&world, ui_event.target,),
                     _ => Trans::None,
                 }
             },
@@ -130,6 +172,10 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for LoadMenuSta
         self.dispatch(&world,);
         data.update_menu(&world,);
 
+        if self.loading_stage.is_some() {
+            return self.update_loading(&mut world,);
+        }
+
         if !self.b_buttons_found {
             self.b_buttons_found =
                 self.insert_button(&mut world, LoadMenuButtons::Back, "menu_load_back_button",)
@@ -137,7 +183,8 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for LoadMenuSta
                         &mut world,
                         LoadMenuButtons::Load,
                         "menu_load_load_button",
-                    );
+                    )
+                    && self.find_texts(&mut world,);
         }
 
         Trans::None
@@ -165,13 +212,13 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for LoadMenuSta
 }
 
 impl<'a, 'b, 'd, 'e,> LoadMenuState<'d, 'e,> {
-    fn btn_click(
-        &self,
-        world: &mut World,
-        target: Entity,
-    ) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+    fn btn_click(&mut self, world: &World, target: Entity,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
         use self::LoadMenuButtons::*;
-        if let Some(button,) = self.ui_buttons.get(&target,) {
+        if self.loading_stage.is_some() {
+            // Already loading a savegame.
+            return Trans::None;
+        }
+        if let Some(button,) = self.ui_buttons.get(&target,).cloned() {
             match button {
                 Back => self.btn_back(),
                 Load => self.btn_load(world,),
@@ -188,36 +235,143 @@ impl<'a, 'b, 'd, 'e,> LoadMenuState<'d, 'e,> {
         Trans::Pop
     }
 
-    fn btn_load(&self, world: &mut World,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+    fn btn_load(&mut self, world: &World,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+        let savegame_name = self
+            .name_text
+            .and_then(|entity| {
+                world
+                    .read_storage::<UiText>()
+                    .get(entity,)
+                    .map(|ui_text| ui_text.text.trim().to_string(),)
+            },)
+            .unwrap_or_default();
+        if savegame_name.is_empty() {
+            self.show_status(world, "Enter the name of the savegame to load.",);
+            return Trans::None;
+        }
+
         #[cfg(feature = "debug")]
-        debug!("Loading savegame.");
+        debug!("Loading savegame {:?}.", savegame_name);
+        self.show_status(world, "",);
+        self.loading_stage = Some(LoadingStage::SessionData(savegame_name,),);
+        Trans::None
+    }
 
-        let mut path = PathBuf::new();
-        path.push("./",);
-        path.push("savegames",);
-        path.push("Mark",);
-        path.push("session_data",);
-        path.set_extension("ron",);
+    // Finds the name field and the status text, returns whether both exist.
+    fn find_texts(&mut self, world: &mut World,) -> bool {
+        let (name_text, status_text,) = world.exec(|finder: UiFinder<'_,>| {
+            (
+                finder.find("menu_load_name_text",),
+                finder.find("menu_load_status_text",),
+            )
+        },);
+        self.name_text = name_text;
+        self.status_text = status_text;
+        name_text.is_some() && status_text.is_some()
+    }
 
-        let render_config = { world.read_resource::<RenderConfig>().clone() };
+    // Shows the given text below the load button, e.g. why loading failed.
+    fn show_status(&self, world: &World, text: &str,) {
+        if let Some(entity,) = self.status_text {
+            if let Some(ui_text,) = world.write_storage::<UiText>().get_mut(entity,) {
+                ui_text.text = text.to_string();
+            }
+        }
+    }
 
-        match GameSessionData::load(path.clone(), &render_config,) {
-            Ok(data,) => {
-                world.add_resource(SavegamePaths::init("./", data.game_name.clone(), false));
-                world.add_resource::<GameSessionData>(data,);
+    // Advances loading the savegame by one step per frame, entering the game once everything is loaded.
+    fn update_loading(&mut self, world: &mut World,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+        match self.loading_stage.take() {
+            Some(LoadingStage::SessionData(savegame_name,),) => {
+                let mut de_savegame = DeSavegameSystem::new(savegame_name,);
+                de_savegame.run_now(&world.res,);
+                // Adds the loaded resources.
+                world.maintain();
+
+                // On errors the player may try again, e.g. with another name.
+                match de_savegame.result() {
+                    Some(Ok(_,),) => self.loading_stage = Some(LoadingStage::Players,),
+                    Some(Err(e,),) => self.show_status(world, &e.to_string(),),
+                    None => {},
+                }
+                Trans::None
             },
-            Err(e,) => {
-                error!("Error loading Savegame data at {:?}: {:?}", path, e);
-                return Trans::None;
+            Some(LoadingStage::Players,) => {
+                init_session(world, Some(&mut self.progress_counter,),);
+                self.loading_stage = Some(LoadingStage::Chunks,);
+                self.enable_dispatcher(world,);
+                self.request_chunks(world,);
+                Trans::None
+            },
+            Some(LoadingStage::Chunks,) => {
+                if let Some(ref mut reader,) = self.chunk_event_reader {
+                    for event in world.read_resource::<EventChannel<ChunkEvent,>>().read(reader,) {
+                        match *event {
+                            ChunkEvent::Loaded(chunk_id,) => {
+                                self.pending_chunks.remove(&chunk_id,);
+                            },
+                            ChunkEvent::FailedLoad(chunk_id,) => {
+                                warn!("| Failed loading {:?}, starting without it.", chunk_id);
+                                self.pending_chunks.remove(&chunk_id,);
+                            },
+                            _ => {},
+                        }
+                    }
+                }
+
+                let b_sprites_loaded = match self.progress_counter.complete() {
+                    Completion::Loading => false,
+                    Completion::Failed => {
+                        error!("| Failed loading assets: {:?}", self.progress_counter.errors());
+                        true
+                    },
+                    Completion::Complete => true,
+                };
+                if !self.pending_chunks.is_empty() || !b_sprites_loaded {
+                    self.loading_stage = Some(LoadingStage::Chunks,);
+                    return Trans::None;
+                }
+
+                #[cfg(feature = "debug")]
+                debug!("Savegame loaded, starting the game.");
+                self.chunk_event_reader = None;
+                let ingame_ui_prefab_handle = Some(
+                    world.exec(|loader: UiLoader<'_,>| loader.load("Prefabs/ui/Ingame/Base.ron", (),),),
+                );
+
+                Trans::Switch(Box::new({
+                    IngameBaseState::new(world, ingame_ui_prefab_handle,)
+                },),)
             },
+            None => Trans::None,
         }
+    }
+
+    // Requests all chunks around the restored players, and starts listening for them being loaded.
+    fn request_chunks(&mut self, world: &mut World,) {
+        let mut chunk_events = world.write_resource::<EventChannel<ChunkEvent,>>();
+        self.chunk_event_reader = Some(chunk_events.register_reader(),);
 
-        let ingame_ui_prefab_handle = Some(
-            world.exec(|loader: UiLoader<'_,>| loader.load("Prefabs/ui/Ingame/Base.ron", (),),),
-        );
+        let session_data = world.read_resource::<GameSessionData>();
+        let render_config = world.read_resource::<RenderConfig>();
+        let transforms = world.read_storage::<Transform>();
+        let players = world.read_storage::<PlayerBase>();
 
-        Trans::Switch(Box::new({
-            IngameBaseState::new(world, ingame_ui_prefab_handle,)
-        },),)
+        self.pending_chunks.clear();
+        for (transform, _,) in (&transforms, &players,).join() {
+            match ChunkIndex::from_transform(transform, &render_config, &session_data.planet,) {
+                Ok(chunk_id,) => {
+                    let chunks = session_data
+                        .planet
+                        .chunks_around(chunk_id, render_config.chunk_render_distance,);
+                    self.pending_chunks.extend(chunks,);
+                },
+                Err(e,) => warn!("| Error calculating a player's ChunkIndex: {:?}", e),
+            }
+        }
+
+        for &chunk_id in self.pending_chunks.iter() {
+            chunk_events.single_write(ChunkEvent::RequestingLoad(chunk_id,),);
+        }
     }
 }
@@ -1,5 +1,7 @@
 use std::{collections::HashMap, u64};
 
+use rand;
+
 use amethyst::{
     assets::Handle,
     core::timing::Time,
@@ -7,7 +9,7 @@ use amethyst::{
     input::{is_close_requested, is_key_down},
     prelude::*,
     renderer::VirtualKeyCode,
-    ui::{UiEventType, UiLoader, UiPrefab},
+    ui::{UiEventType, UiFinder, UiLoader, UiPrefab, UiText},
 };
 
 use crate::{
@@ -16,34 +18,47 @@ use crate::{
     ToppaGameData,
 };
 
-#[derive(PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Clone, Copy)]
 pub enum NewGameButtons {
     Back,
     CreateNewGame,
+    SkipUnmodifiedChunks,
 }
 
 struct GameInfo {
-    pub name:       &'static str,
-    pub planet_dim: (u64, u64,),
-    pub chunk_dim:  (u64, u64,),
+    pub name:                   &'static str,
+    pub planet_dim:             (u64, u64,),
+    pub chunk_dim:              (u64, u64,),
+    pub seed:                   u64,
+    /// See [`Planet::skip_unmodified_chunks`](../../resources/ingame/game_world/struct.Planet.html#structfield.skip_unmodified_chunks).
+    pub skip_unmodified_chunks: bool,
 }
 
 impl Default for GameInfo {
     fn default() -> Self {
         GameInfo {
-            name:       "Terra Incognita",
-            planet_dim: (16, 16,),
-            chunk_dim:  (16, 32,),
+            name:                   "Terra Incognita",
+            planet_dim:             (16, 16,),
+            chunk_dim:              (16, 32,),
+            seed:                   rand::random(),
+            skip_unmodified_chunks: false,
         }
     }
 }
 
 impl GameInfo {
-    pub fn new(name: &'static str, planet_dim: (u64, u64,), chunk_dim: (u64, u64,),) -> Self {
+    pub fn new(
+        name: &'static str,
+        planet_dim: (u64, u64,),
+        chunk_dim: (u64, u64,),
+        seed: u64,
+    ) -> Self {
         GameInfo {
             name,
             planet_dim,
             chunk_dim,
+            seed,
+            skip_unmodified_chunks: false,
         }
     }
 }
@@ -59,8 +74,10 @@ pub struct NewGameState<'d, 'e,> {
     // The Handle of the Prefab for the displayed Ui Entity.
     current_screen_prefab: Option<Handle<UiPrefab,>,>,
     // Map of the Ui Button entities and the corresponding button type.
-    ui_buttons:      HashMap<Entity, NewGameButtons,>,
-    b_buttons_found: bool,
+    ui_buttons:           HashMap<Entity, NewGameButtons,>,
+    b_buttons_found:      bool,
+    // The text showing whether unmodified chunks get skipped.
+    skip_unmodified_text: Option<Entity,>,
 
     // Info specific to the game about to be created.
     // e.g. the player count, names, etc...
@@ -90,9 +107,10 @@ impl<'d, 'e,> ToppaState<'d, 'e,> for NewGameState<'d, 'e,> {
             current_screen_prefab: screen_opt,
             ui_buttons:            HashMap::new(),
             b_buttons_found:       false,
+            skip_unmodified_text:  None,
             main_dispatcher:       None,
             // TODO: Create buttons to override the fields of game_info
-            game_info:             GameInfo::new("Mark", (16, 16,), (16, 16,),),
+            game_info:             GameInfo::new("Mark", (16, 16,), (16, 16,), rand::random(),),
         }
     }
 
@@ -176,7 +194,15 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for NewGameStat
                         &mut world,
                         NewGameButtons::CreateNewGame,
                         "menu_newgame_creategame_button",
+                    )
+                    && self.insert_button(
+                        &mut world,
+                        NewGameButtons::SkipUnmodifiedChunks,
+                        "menu_newgame_skip_unmodified_button",
                     );
+            self.skip_unmodified_text =
+                world.exec(|finder: UiFinder<'_,>| finder.find("menu_newgame_skip_unmodified_text",),);
+            self.show_skip_unmodified(&world,);
         }
 
         Trans::None
@@ -205,15 +231,17 @@ impl<'a, 'b, 'd, 'e,> State<ToppaGameData<'a, 'b,>, StateEvent,> for NewGameStat
 
 impl<'a, 'b, 'd, 'e,> NewGameState<'d, 'e,> {
     fn btn_click(
-        &self,
+        &mut self,
         world: &mut World,
         target: Entity,
     ) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
         use self::NewGameButtons::*;
-        if let Some(button,) = self.ui_buttons.get(&target,) {
+        let button_opt = self.ui_buttons.get(&target,).cloned();
+        if let Some(button,) = button_opt {
             match button {
                 Back => self.btn_back(),
                 CreateNewGame => self.btn_creategame(world,),
+                SkipUnmodifiedChunks => self.btn_skip_unmodified(world,),
             }
         }
         else {
@@ -227,18 +255,40 @@ impl<'a, 'b, 'd, 'e,> NewGameState<'d, 'e,> {
         Trans::Pop
     }
 
+    fn btn_skip_unmodified(&mut self, world: &World,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
+        self.game_info.skip_unmodified_chunks = !self.game_info.skip_unmodified_chunks;
+        self.show_skip_unmodified(world,);
+        Trans::None
+    }
+
+    // Shows whether the game about to be created skips unmodified chunks.
+    fn show_skip_unmodified(&self, world: &World,) {
+        if let Some(entity,) = self.skip_unmodified_text {
+            if let Some(ui_text,) = world.write_storage::<UiText>().get_mut(entity,) {
+                ui_text.text = if self.game_info.skip_unmodified_chunks {
+                    "Unmodified chunks: generated again".to_string()
+                }
+                else {
+                    "Unmodified chunks: saved".to_string()
+                };
+            }
+        }
+    }
+
     fn btn_creategame(&self, world: &mut World,) -> Trans<ToppaGameData<'a, 'b,>, StateEvent,> {
         #[cfg(feature = "debug")]
         debug!("Creating new game.");
         // NOTE: Think about how to do this better
 
         let ren_con = &world.read_resource::<RenderConfig>().clone();
-        let session_data = GameSessionData::new(
+        let mut session_data = GameSessionData::new(
             self.game_info.name.to_string(),
             self.game_info.planet_dim,
             self.game_info.chunk_dim,
+            self.game_info.seed,
             ren_con,
         );
+        session_data.planet.skip_unmodified_chunks = self.game_info.skip_unmodified_chunks;
         world.add_resource::<GameSessionData>(session_data,);
         world.add_resource(SavegamePaths::init("./", self.game_info.name.to_string(), true));
 
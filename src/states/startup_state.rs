@@ -1,6 +1,9 @@
 use amethyst::{
     assets::{Completion, Handle, ProgressCounter},
-    core::timing::Time,
+    core::{
+        specs::saveload::{U64Marker, U64MarkerAllocator},
+        timing::Time,
+    },
     ecs::prelude::Entity,
     input::{is_close_requested, is_key_down},
     prelude::*,
@@ -10,8 +13,12 @@ use amethyst::{
 };
 
 use crate::{
-    components::{for_characters::TagGenerator, IsIngameEntity},
-    events::planet_events::ChunkEvent,
+    components::{
+        for_characters::{PartOf, TagGenerator},
+        IsIngameEntity,
+    },
+    entities::player_parts::PlayerParts,
+    events::planet_events::{ChunkEvent, TileEvent},
     resources::{GameSprites, RenderConfig},
     states::{main_menu, ToppaState},
     ToppaGameData,
@@ -165,7 +172,13 @@ impl<'a, 'b,> State<ToppaGameData<'a, 'b,>, StateEvent,> for StartupState {
         world.add_resource::<TagGenerator>(TagGenerator::default(),);
         world.add_resource::<GameSprites>(GameSprites::default(),);
         world.add_resource(EventChannel::<ChunkEvent,>::new(),);
+        world.add_resource(EventChannel::<TileEvent,>::new(),);
+        world.add_resource(U64MarkerAllocator::new(),);
         world.register::<IsIngameEntity>();
+        // Needed to save and load players.
+        world.register::<U64Marker>();
+        world.register::<PlayerParts>();
+        world.register::<PartOf>();
     }
 
     // For the sake of completeness:
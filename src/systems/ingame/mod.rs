@@ -3,9 +3,10 @@ mod engine_force;
 mod gravitation;
 mod movement;
 mod player_position;
+mod world_wrap;
 
 pub use self::{
     cleanup_on_close::CleanupOnCloseSystem, engine_force::EngineForceSystem,
     gravitation::GravitationSystem, movement::MovementSystem,
-    player_position::PlayerPositionSystem,
+    player_position::PlayerPositionSystem, world_wrap::WorldWrapSystem,
 };
@@ -4,8 +4,9 @@ use std::{collections::HashSet, u64};
 
 use amethyst::{
     core::transform::components::Transform,
-    ecs::{Join, Read, ReadStorage, System, Write, WriteStorage},
-    shrev::EventChannel,
+    ecs::{Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+    shred::Resources,
+    shrev::{EventChannel, ReaderId},
 };
 
 use crate::{
@@ -24,16 +25,19 @@ use crate::{
 /// Calculates the players position expressed in [`ChunkIndex`](struct.ChunkIndex.html) and [`TileIndex`](struct.TileIndex.html).
 /// Tries to calculate new `TileIndex` based on current `Transform` and previous `Position.chunk`-ChunkIndex.
 /// If that fails, calculates new ChunkIndex based only on current `Transform`, and then the new `TileIndex`.
+/// After the planet was re-chunked, all chunks around the players get requested again.
 pub struct PlayerPositionSystem {
-    prev_chunks: HashSet<ChunkIndex,>,
-    cur_chunks:  HashSet<ChunkIndex,>,
+    event_reader: Option<ReaderId<ChunkEvent,>,>,
+    prev_chunks:  HashSet<ChunkIndex,>,
+    cur_chunks:   HashSet<ChunkIndex,>,
 }
 
 impl Default for PlayerPositionSystem {
     fn default() -> Self {
         PlayerPositionSystem {
-            prev_chunks: HashSet::with_capacity(9,),
-            cur_chunks:  HashSet::with_capacity(9,),
+            event_reader: None,
+            prev_chunks:  HashSet::with_capacity(9,),
+            cur_chunks:   HashSet::with_capacity(9,),
         }
     }
 }
@@ -62,6 +66,28 @@ impl<'s,> System<'s,> for PlayerPositionSystem {
         if let (Some(session_data,), Some(render_config,), Some(mut chunk_event_channel,),) =
             (session_data, render_config, chunk_event_channel,)
         {
+            if let Some(ref mut event_reader,) = self.event_reader {
+                let rechunked = chunk_event_channel.read(event_reader,).any(|event| {
+                    match event {
+                        ChunkEvent::Rechunked(_,) | ChunkEvent::FailedRechunk(_,) => true,
+                        _ => false,
+                    }
+                },);
+
+                if rechunked {
+                    // All chunks were unloaded.
+                    self.prev_chunks.clear();
+                }
+            }
+
+            if self.prev_chunks.is_empty() {
+                // No chunks requested yet, e.g. after re-chunking or for players loaded from a savegame,
+                // whose stored chunk index would otherwise count as already loaded.
+                for (_player, mut player_pos,) in (&players, &mut player_positions,).join() {
+                    player_pos.chunk = ChunkIndex(u64::MAX, u64::MAX,);
+                }
+            }
+
             for (transform, _player, mut player_pos,) in
                 (&transforms, &players, &mut player_positions,).join()
             {
@@ -120,61 +146,12 @@ impl<'s,> System<'s,> for PlayerPositionSystem {
                                                 player_pos.tile = tile_index;
                                                 player_pos.chunk = chunk_index;
 
-                                                //dealing with over- and underflow
-                                                let lower_y = {
-                                                    if chunk_index.0
-                                                        >= render_config.chunk_render_distance
-                                                    {
-                                                        chunk_index.0
-                                                            - render_config.chunk_render_distance
-                                                    }
-                                                    else {
-                                                        0
-                                                    }
-                                                };
-                                                let lower_x = {
-                                                    if chunk_index.1
-                                                        >= render_config.chunk_render_distance
-                                                    {
-                                                        chunk_index.1
-                                                            - render_config.chunk_render_distance
-                                                    }
-                                                    else {
-                                                        // TODO: World-wrapping
-                                                        0
-                                                    }
-                                                };
-
-                                                let upper_y = {
-                                                    let buff = chunk_index.0
-                                                        + render_config.chunk_render_distance;
-                                                    if buff >= chunk_index.0 {
-                                                        buff
-                                                    }
-                                                    else {
-                                                        u64::MAX
-                                                    }
-                                                };
-                                                let upper_x = {
-                                                    let buff = chunk_index.1
-                                                        + render_config.chunk_render_distance;
-                                                    if buff >= chunk_index.1 {
-                                                        buff
-                                                    }
-                                                    else {
-                                                        // TODO: World-wrapping
-                                                        u64::MAX
-                                                    }
-                                                };
-
                                                 // Populating the current chunk HashSet
-                                                for y in lower_y ..= upper_y {
-                                                    for x in lower_x ..= upper_x {
-                                                        // No need to check the returned boolean, as the HashSet has been `.drain()`ed previously.
-                                                        let chunk_id = ChunkIndex(y, x,);
-                                                        self.cur_chunks.insert(chunk_id,);
-                                                    }
-                                                }
+                                                // No need to check the returned booleans, as the HashSet has been `.drain()`ed previously.
+                                                self.cur_chunks.extend(planet_ref.chunks_around(
+                                                    chunk_index,
+                                                    render_config.chunk_render_distance,
+                                                ),);
                                                 // Comparing the current and previous HashSets (`.difference()` returns only those NOT present in the other)
                                                 //let cur_chunks = self.cur_chunks.clone();
                                                 //let prev_chunks = self.prev_chunks.clone();
@@ -241,4 +218,13 @@ impl<'s,> System<'s,> for PlayerPositionSystem {
             error!("| Resources not found.");
         }
     }
+
+    fn setup(&mut self, res: &mut Resources,) {
+        Self::SystemData::setup(res,);
+
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<ChunkEvent,>>()
+                .register_reader(),
+        );
+    }
 }
@@ -0,0 +1,108 @@
+//! Lets the player fly around the planet, and keeps it above the bedrock.
+
+use amethyst::{
+    core::{nalgebra::Vector3, transform::components::Transform},
+    ecs::{Join, Read, ReadStorage, System, WriteStorage},
+};
+
+use crate::{
+    components::{
+        for_characters::PlayerBase,
+        for_ground_entities::TileBase,
+        physics::Dynamics,
+    },
+    resources::{
+        ingame::{
+            game_world::{generation::WorldGenTables, GlobalTileIndex},
+            GameSessionData,
+        },
+        RenderConfig,
+    },
+};
+
+/// The planet wraps around in x-direction, and the chunks close to the seam get placed on the player's side of it.
+/// Whenever the player crosses the seam, this system moves the player and all loaded tiles by the planet's width,
+/// so the player's x-translation stays within the planet and nothing visibly changes.
+/// Children of the player, like the camera, drill and tracks, follow on their own.
+///
+/// At the bottom of the planet the bedrock stops the player,
+/// it cannot get below the row right above the bedrock of the column it is in.
+#[derive(Default)]
+pub struct WorldWrapSystem;
+
+impl<'s,> System<'s,> for WorldWrapSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform,>,
+        WriteStorage<'s, Dynamics,>,
+        ReadStorage<'s, PlayerBase,>,
+        ReadStorage<'s, TileBase,>,
+        Option<Read<'s, GameSessionData,>,>,
+        Option<Read<'s, RenderConfig,>,>,
+        Option<Read<'s, WorldGenTables,>,>,
+    );
+
+    fn run(
+        &mut self,
+        (mut transforms, mut dynamics, players, tiles, session_data, render_config, world_gen_tables,): Self::SystemData,
+    ) {
+        if let (Some(session_data,), Some(render_config,), Some(world_gen_tables,),) =
+            (session_data, render_config, world_gen_tables,)
+        {
+            let planet = &session_data.planet;
+
+            // Bedrock
+            for (transform, dynamic, _,) in (&mut transforms, &mut dynamics, &players,).join() {
+                // Row 0 always exists, so this only wraps the column.
+                let column = match GlobalTileIndex::from_translation(
+                    &Vector3::new(transform.translation().x, 0.0, 0.0,),
+                    &render_config,
+                    planet,
+                ) {
+                    Ok(tile,) => tile.1,
+                    Err(_,) => continue,
+                };
+                let bedrock_depth = world_gen_tables.core.bedrock_depth_at(planet.seed, column,);
+                let lowest_row = planet.tile_count().0.saturating_sub(bedrock_depth + 1,);
+                let lowest_y = GlobalTileIndex(lowest_row, column,).to_translation(&render_config,).y;
+
+                let y_transl = transform.translation().y;
+                if y_transl > lowest_y {
+                    transform.move_global(Vector3::new(0.0, lowest_y - y_transl, 0.0,),);
+                    if dynamic.vel.y > 0.0 {
+                        dynamic.vel.y = 0.0;
+                    }
+                }
+            }
+
+            // World-wrapping
+            let width = planet.width(&render_config,);
+            if width <= 0.0 {
+                return;
+            }
+
+            // The seam lies at the left edge of the first column's sprites.
+            let seam = -0.5 * render_config.tile_size.1;
+            let laps = match (&transforms, &players,).join().next() {
+                Some((transform, _,),) => ((transform.translation().x - seam) / width).floor(),
+                None => return,
+            };
+            if laps == 0.0 {
+                return;
+            }
+
+            #[cfg(feature = "debug")]
+            debug!("| Player crossed the planet's seam, wrapping by {} laps.", laps);
+
+            let shift = Vector3::new(-laps * width, 0.0, 0.0,);
+            for (transform, _,) in (&mut transforms, &players,).join() {
+                transform.move_global(shift,);
+            }
+            for (transform, _,) in (&mut transforms, &tiles,).join() {
+                transform.move_global(shift,);
+            }
+        }
+        else {
+            error!("| Resources not found.");
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::SystemTime,
+};
+
+use amethyst::{
+    core::{specs::saveload::U64Marker, timing::Time},
+    ecs::prelude::*,
+};
+
+use crate::{
+    entities::tile::TileTypes,
+    resources::{
+        ingame::{
+            game_world::{ChunkIndex, GlobalTileIndex, Planet, TileIndex},
+            GameSessionData, SaveTransaction, SavegamePaths,
+        },
+        AutosaveConfig, RenderConfig,
+    },
+};
+
+use super::ser::{players_file_text, PlayerStorages};
+
+/// Saves the game on a timer and whenever a player returns to the surface, as set in the [AutosaveConfig](../../resources/struct.AutosaveConfig.html).
+/// Each autosave goes to its own slot next to the manual save, replacing the oldest one,
+/// see [`SavegamePaths::autosave_slot`](../../resources/ingame/struct.SavegamePaths.html#method.autosave_slot).
+///
+/// Only a snapshot of the session, the players and the changed chunks is taken during the frame,
+/// the files get written on another thread. Only the tiles of the loaded chunks get copied for it,
+/// the unloaded ones are shared with the planet, so the snapshot does not grow with the explored area.
+/// Chunks are not marked as saved, so the manual save still writes everything changed since it was saved the last time.
+/// No new autosave starts while the previous one is still being written, it follows once that one is done.
+pub struct AutosaveSystem {
+    // Playing time since the last autosave started.
+    elapsed:        f32,
+    // Whether any player was above the surface during the last frame, unknown in the first one.
+    prev_surface:   Option<bool,>,
+    b_requested:    bool,
+    // Receives the result of the autosave currently being written, if any.
+    pending_result: Option<Receiver<Result<PathBuf, (),>,>,>,
+}
+
+impl Default for AutosaveSystem {
+    fn default() -> Self {
+        AutosaveSystem {
+            elapsed:        0.0,
+            prev_surface:   None,
+            b_requested:    false,
+            pending_result: None,
+        }
+    }
+}
+
+impl<'a,> System<'a,> for AutosaveSystem {
+    type SystemData = (
+        Read<'a, Time,>,
+        Option<Read<'a, AutosaveConfig,>,>,
+        Option<Read<'a, GameSessionData,>,>,
+        Option<Read<'a, SavegamePaths,>,>,
+        Option<Read<'a, RenderConfig,>,>,
+        Entities<'a,>,
+        ReadStorage<'a, U64Marker,>,
+        PlayerStorages<'a,>,
+    );
+
+    fn run(
+        &mut self,
+        (time, config, session_data, paths, render_config, entities, markers, player_storages,): Self::SystemData,
+    ) {
+        self.poll_pending_result();
+
+        if let (Some(config,), Some(session_data,), Some(paths,), Some(render_config,),) =
+            (config, session_data, paths, render_config,)
+        {
+            if !config.enabled {
+                return;
+            }
+
+            self.elapsed += time.delta_seconds();
+            if config.interval > 0.0 && self.elapsed >= config.interval {
+                self.b_requested = true;
+            }
+
+            let planet = &session_data.planet;
+            let b_on_surface = (&player_storages.0, &player_storages.3,).join().any(|(_, transform,)| {
+                match GlobalTileIndex::from_transform(transform, &render_config, planet,) {
+                    Ok(tile,) => planet.is_above_surface(tile,),
+                    // Only the rows above the planet are out of bounds, the bottom ones are not reachable.
+                    Err(_,) => transform.translation().y < 0.0,
+                }
+            },);
+            if config.on_surface
+                && b_on_surface
+                && self.prev_surface == Some(false,)
+                && self.elapsed >= config.surface_cooldown
+            {
+                #[cfg(feature = "debug")]
+                debug!("| Returned to the surface, autosaving.");
+                self.b_requested = true;
+            }
+            self.prev_surface = Some(b_on_surface,);
+
+            if !self.b_requested || self.pending_result.is_some() {
+                return;
+            }
+
+            let players_text = match players_file_text(&entities, &markers, &player_storages,) {
+                Ok(text,) => text,
+                Err(_,) => {
+                    error!("| Autosave skipped, since the players could not be serialized.");
+                    self.b_requested = false;
+                    self.elapsed = 0.0;
+                    return;
+                },
+            };
+            let snapshot = AutosaveSnapshot {
+                game_name: session_data.game_name.clone(),
+                paths: (*paths).clone(),
+                planet: planet.without_chunks(),
+                session_text: session_data.session_file_text(),
+                players_text,
+                chunks: planet
+                    .iter_chunks()
+                    .filter(|(_, chunk,)| chunk.is_dirty(),)
+                    .map(|(&chunk_id, chunk,)| (chunk_id, chunk.tile_types().clone(),),)
+                    .collect(),
+                slot_count: config.slot_count,
+            };
+
+            let (sender, receiver,) = mpsc::channel();
+            let spawned = thread::Builder::new()
+                .name("autosave".to_string(),)
+                .spawn(move || {
+                    let _ = sender.send(snapshot.write(),);
+                },);
+            match spawned {
+                Ok(_,) => self.pending_result = Some(receiver,),
+                Err(e,) => error!("| Could not start autosaving: {:?}", e),
+            }
+            self.b_requested = false;
+            self.elapsed = 0.0;
+        }
+    }
+}
+
+// private methods
+impl AutosaveSystem {
+    fn poll_pending_result(&mut self) {
+        let finished = match self.pending_result {
+            Some(ref receiver,) => {
+                match receiver.try_recv() {
+                    Ok(Ok(slot_path,),) => {
+                        info!("| Autosaved to {:?}.", slot_path);
+                        true
+                    },
+                    Ok(Err(_,),) => {
+                        error!("| Autosaving failed, the previous autosaves are kept.");
+                        true
+                    },
+                    Err(TryRecvError::Empty,) => false,
+                    Err(TryRecvError::Disconnected,) => {
+                        error!("| Autosaving stopped without finishing.");
+                        true
+                    },
+                }
+            },
+            None => false,
+        };
+        if finished {
+            self.pending_result = None;
+        }
+    }
+}
+
+// Everything an autosave needs, taken from the game during a single frame.
+struct AutosaveSnapshot {
+    game_name:    String,
+    // The paths of the manual save, whose chunks the autosave is based on.
+    paths:        SavegamePaths,
+    planet:       Planet,
+    session_text: String,
+    players_text: String,
+    // The tiles of all loaded chunks changed since the manual save, the unloaded ones are part of the planet.
+    chunks:       Vec<(ChunkIndex, BTreeMap<TileIndex, TileTypes,>,),>,
+    slot_count:   u32,
+}
+
+impl AutosaveSnapshot {
+    // Writes the snapshot to the oldest autosave slot as one transaction. Runs on the autosave thread.
+    fn write(self) -> Result<PathBuf, (),> {
+        let slot_paths = match self.choose_slot() {
+            Some(slot_paths,) => slot_paths,
+            None => {
+                error!("| No autosave slot available for {:?}.", self.game_name);
+                return Err((),);
+            },
+        };
+        if let Err(e,) = fs::create_dir_all(&slot_paths.chunk_dir_path,) {
+            error!("| Could not create {:?}: {:?}", slot_paths.chunk_dir_path, e);
+            return Err((),);
+        }
+
+        let mut transaction = SaveTransaction::new(&slot_paths.savegame_dir_path,)
+            .map_err(|e| error!("| Could not start autosaving to {:?}: {:?}", slot_paths.savegame_dir_path, e),)?;
+
+        // The chunks of the manual save, unchanged chunks are not part of the snapshot.
+        if let Err(e,) = copy_chunk_files(&self.paths.chunk_dir_path, &slot_paths.chunk_dir_path, &mut transaction,) {
+            error!(
+                "| Copying the chunks of {:?} to {:?} failed: {:?}",
+                self.paths.chunk_dir_path, slot_paths.chunk_dir_path, e
+            );
+            return Err((),);
+        }
+        for &(chunk_id, ref tiles,) in self.chunks.iter() {
+            self.planet
+                .write_chunk_tiles(&slot_paths.chunk_dir_path, chunk_id, tiles, &mut transaction,)
+                .map_err(|_| (),)?;
+        }
+        // The planet's copy holds the chunks unloaded since the manual save.
+        self.planet
+            .stage_unsaved_chunks(&slot_paths.chunk_dir_path, &mut transaction,)
+            .map_err(|_| (),)?;
+
+        for &(file_path, text,) in [
+            (&slot_paths.savegame_file_path, &self.session_text,),
+            (&slot_paths.players_file_path, &self.players_text,),
+        ]
+        .iter()
+        {
+            if let Err(e,) = transaction.write(file_path, text.as_bytes(),) {
+                error!("| Writing {:?} threw error: {:?}", file_path, e);
+                return Err((),);
+            }
+        }
+
+        if let Err(e,) = transaction.commit() {
+            error!("| Committing autosave {:?} failed: {:?}", slot_paths.savegame_dir_path, e);
+            return Err((),);
+        }
+        Ok(slot_paths.savegame_dir_path,)
+    }
+
+    // The first slot not used yet, otherwise the one autosaved to the longest time ago.
+    // A slot that was loaded as the current savegame is never overwritten.
+    fn choose_slot(&self) -> Option<SavegamePaths,> {
+        let mut oldest: Option<(SystemTime, SavegamePaths,),> = None;
+        for slot in 1 ..= self.slot_count {
+            let slot_paths = self.paths.autosave_slot(&self.game_name, slot,);
+            if slot_paths.savegame_dir_path == self.paths.savegame_dir_path {
+                continue;
+            }
+
+            let modified = match fs::metadata(&slot_paths.savegame_file_path,).and_then(|meta| meta.modified(),) {
+                Ok(modified,) => modified,
+                Err(_,) => return Some(slot_paths,),
+            };
+            let b_older = oldest.as_ref().map_or(true, |&(oldest_modified, _,)| modified < oldest_modified,);
+            if b_older {
+                oldest = Some((modified, slot_paths,),);
+            }
+        }
+        oldest.map(|(_, slot_paths,)| slot_paths,)
+    }
+}
+
+// Stages a copy of every chunk and region file of the source directory in the target directory,
+// and removes the target's files the source does not have.
+fn copy_chunk_files(source_dir_path: &Path, target_dir_path: &Path, transaction: &mut SaveTransaction,) -> io::Result<(),> {
+    let mut copied_names = Vec::new();
+    match fs::read_dir(source_dir_path,) {
+        Ok(entries,) => {
+            for entry in entries.filter_map(|entry| entry.ok(),) {
+                let source_path = entry.path();
+                // Files staged by a save currently being written are not part of the manual save yet.
+                if !source_path.is_file() || source_path.extension().map_or(false, |extension| extension == "tmp",) {
+                    continue;
+                }
+                let file_name = entry.file_name();
+                transaction.write(&target_dir_path.join(&file_name,), &fs::read(&source_path,)?,)?;
+                copied_names.push(file_name,);
+            }
+        },
+        // A new game that was never saved has no chunk files yet.
+        Err(ref e,) if e.kind() == io::ErrorKind::NotFound => {},
+        Err(e,) => return Err(e,),
+    }
+
+    for entry in fs::read_dir(target_dir_path,)?.filter_map(|entry| entry.ok(),) {
+        if entry.path().is_file() && !copied_names.contains(&entry.file_name(),) {
+            transaction.remove(&entry.path(),);
+        }
+    }
+    Ok((),)
+}
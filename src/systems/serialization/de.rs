@@ -10,7 +10,14 @@ use ron;
 use serde::Serializer;
 
 use amethyst::{
-    core::{timing::Time, transform::components::Transform},
+    core::{
+        specs::{
+            error::NoError,
+            saveload::{DeserializeComponents, U64Marker, U64MarkerAllocator},
+        },
+        timing::Time,
+        transform::components::Transform,
+    },
     ecs::prelude::*,
     renderer::SpriteRender,
     shred::Resources,
@@ -18,70 +25,161 @@ use amethyst::{
 };
 
 use crate::{
-    components::{for_characters::PlayerBase, for_ground_entities::TileBase},
-    entities::tile::TileTypes,
+    components::{
+        for_characters::{player::Position, Engine, FuelTank, PartOf, PlayerBase},
+        for_ground_entities::TileBase,
+        physics::{Dynamics, PhysicalProperties},
+    },
+    entities::{player_parts::PlayerParts, tile::TileTypes},
     events::planet_events::ChunkEvent,
     resources::{
         ingame::{
             game_world::{Chunk, ChunkIndex, Planet, TileGenerationStorages, TileIndex},
-            GameSessionData, SavegamePaths,
+            GameSessionData, SavegameError, SavegamePaths,
         },
         GameSprites, RenderConfig,
     },
 };
 
-/// TODO: Everything.
-#[allow(dead_code)]
-pub struct DeSavegameSystem;
+/// Loads the session data of a savegame from its `session_data.ron`, upgrading older savegames,
+/// see [GameSessionData::load_savegame](struct.GameSessionData.html#method.load_savegame).
+/// Replaces the `GameSessionData` and `SavegamePaths` resources at the end of the frame,
+/// since resources cannot be added from inside a system.
+/// Players and chunks are loaded afterwards, see [DePlayersSystem](struct.DePlayersSystem.html)
+/// and the [HotChunkSystem](struct.HotChunkSystem.html).
+pub struct DeSavegameSystem {
+    savegame_name: String,
+    result:        Option<Result<(), SavegameError,>,>,
+}
+
+impl DeSavegameSystem {
+    pub fn new(savegame_name: String,) -> Self {
+        DeSavegameSystem {
+            savegame_name,
+            result: None,
+        }
+    }
+
+    /// The outcome of the last run, `None` if the system did not run yet.
+    pub fn result(&self) -> Option<&Result<(), SavegameError,>,> {
+        self.result.as_ref()
+    }
+}
 
 impl<'a,> System<'a,> for DeSavegameSystem {
+    type SystemData = (Option<Read<'a, RenderConfig,>,>, Read<'a, LazyUpdate,>,);
+
+    fn run(&mut self, (render_config, lazy_update,): Self::SystemData,) {
+        #[cfg(feature = "debug")]
+        debug!("+------------");
+
+        if let Some(render_config,) = render_config {
+            // Names entered by the player must not create empty savegame directories.
+            match SavegamePaths::existing("./", &self.savegame_name,) {
+                Some(paths,) => {
+                    match GameSessionData::load_savegame(&paths, &render_config,) {
+                        Ok(data,) => {
+                            lazy_update.exec_mut(move |world| {
+                                world.add_resource::<GameSessionData>(data,);
+                                world.add_resource(paths,);
+                            },);
+                            self.result = Some(Ok((),),);
+                        },
+                        Err(e,) => {
+                            error!(
+                                "Error loading savegame data at {:?}: {}",
+                                paths.savegame_file_path.clone(),
+                                e
+                            );
+                            self.result = Some(Err(e,),);
+                        },
+                    }
+                },
+                None => {
+                    error!("| Savegame {:?} not found.", self.savegame_name);
+                    self.result = Some(Err(SavegameError::NotFound(self.savegame_name.clone(),),),);
+                },
+            }
+        }
+        else {
+            error!("| RenderConfig not found.");
+        }
+
+        #[cfg(feature = "debug")]
+        debug!("+------------");
+    }
+}
+
+/// Loads the players and their ship parts of the current savegame from `players.ron`,
+/// as written by the [SerSavegameSystem](struct.SerSavegameSystem.html).
+/// Only restores the stored components, the rest gets added by
+/// [restore_players](../../entities/player_parts/fn.restore_players.html) afterwards.
+/// Does nothing if the savegame contains no players.
+/// Needs no version check of its own, since the [DeSavegameSystem](struct.DeSavegameSystem.html) already refused
+/// or upgraded the savegame based on the `format_version` of its session data, which covers `players.ron` as well.
+pub struct DePlayersSystem;
+
+impl<'a,> System<'a,> for DePlayersSystem {
     type SystemData = (
-        Option<Write<'a, GameSessionData,>,>,
         Option<Read<'a, SavegamePaths,>,>,
-        Option<Read<'a, RenderConfig,>,>,
+        Entities<'a,>,
+        WriteStorage<'a, U64Marker,>,
+        Write<'a, U64MarkerAllocator,>,
+        (
+            WriteStorage<'a, PlayerBase,>,
+            WriteStorage<'a, PlayerParts,>,
+            WriteStorage<'a, PartOf,>,
+            WriteStorage<'a, Transform,>,
+            WriteStorage<'a, Position,>,
+            WriteStorage<'a, Dynamics,>,
+            WriteStorage<'a, PhysicalProperties,>,
+            WriteStorage<'a, Engine,>,
+            WriteStorage<'a, FuelTank,>,
+        ),
     );
 
-    fn run(&mut self, (session_data, paths, render_config,): Self::SystemData,) {
+    fn run(
+        &mut self,
+        (paths, entities, mut markers, mut allocator, mut player_storages,): Self::SystemData,
+    ) {
         #[cfg(feature = "debug")]
         debug!("+------------");
 
-        match (session_data, paths, render_config,) {
-            (Some(mut session_data,), Some(paths,), Some(render_config,),) => {
-                match GameSessionData::load(paths.savegame_file_path.clone(), &render_config,) {
-                    Ok(data,) => {
-                        let mut buffer = session_data.deref_mut();
-                        *buffer = data;
+        if let Some(paths,) = paths {
+            if paths.players_file_path.is_file() {
+                match fs::read_to_string(&paths.players_file_path,) {
+                    Ok(text,) => {
+                        let mut de_players = ron::de::Deserializer::from_str(&text,);
+                        if let Err(e,) = DeserializeComponents::<NoError, U64Marker,>::deserialize(
+                            &mut player_storages,
+                            &entities,
+                            &mut markers,
+                            &mut allocator,
+                            &mut de_players,
+                        ) {
+                            error!(
+                                "| Error deserializing players at {:?}: {:?}",
+                                paths.players_file_path.clone(),
+                                e
+                            );
+                        }
                     },
                     Err(e,) => {
                         error!(
-                            "Error loading savegame data at {:?}: {:?}",
-                            paths.savegame_file_path.clone(),
+                            "| Error reading players at {:?}: {:?}",
+                            paths.players_file_path.clone(),
                             e
                         );
                     },
                 }
-            },
-            (None, Some(paths,), Some(render_config,),) => {
-                match GameSessionData::load(paths.savegame_file_path.clone(), &render_config,) {
-                    Ok(data,) => {
-                        /*
-                        let mut buffer = session_data;
-                        buffer = Some(data);
-                        */
-                        error!("Found savegame data at {:?}, but cannot add resource from inside system.", paths.savegame_file_path.clone());
-                    },
-                    Err(e,) => {
-                        error!(
-                            "Error loading savegame data at {:?}: {:?}",
-                            paths.savegame_file_path.clone(),
-                            e
-                        );
-                    },
-                }
-            },
-            _ => {
-                error!("| SavegamePaths not found.");
-            },
+            }
+            else {
+                #[cfg(feature = "debug")]
+                debug!("| Savegame contains no players.");
+            }
+        }
+        else {
+            error!("| SavegamePaths not found.");
         }
 
         #[cfg(feature = "debug")]
@@ -20,10 +20,13 @@ use amethyst::{
 use crate::{
     components::{for_characters::PlayerBase, for_ground_entities::TileBase, IsIngameEntity},
     entities::tile::TileTypes,
-    events::planet_events::ChunkEvent,
+    events::planet_events::{ChunkEvent, TileEvent},
     resources::{
         ingame::{
-            game_world::{Chunk, ChunkIndex, Planet, TileGenerationStorages, TileIndex},
+            game_world::{
+                generation::WorldGenTables, Chunk, ChunkIndex, GlobalTileIndex, Planet,
+                TileGenerationStorages, TileIndex,
+            },
             GameSessionData, SavegamePaths,
         },
         GameSprites, RenderConfig,
@@ -31,18 +34,30 @@ use crate::{
 };
 
 /// TODO: Deletion of initial chunks not working properly.
+/// Loads and unloads chunks as requested by `ChunkEvent`s, and re-chunks the planet if requested.
+/// Answers every load request with `ChunkEvent::Loaded` or `ChunkEvent::FailedLoad`, chunks already loaded are kept.
+/// Afterwards applies the tile changes and deletions requested by `TileEvent`s,
+/// since it has access to all storages needed to create tiles.
 pub struct HotChunkSystem {
-    event_reader:     Option<ReaderId<ChunkEvent,>,>,
-    chunks_to_load:   Vec<ChunkIndex,>,
-    chunks_to_unload: Vec<ChunkIndex,>,
+    event_reader:      Option<ReaderId<ChunkEvent,>,>,
+    tile_event_reader: Option<ReaderId<TileEvent,>,>,
+    chunks_to_load:    Vec<ChunkIndex,>,
+    chunks_to_unload:  Vec<ChunkIndex,>,
+    rechunk_to:        Option<(u64, u64,),>,
+    tiles_to_change:   Vec<(GlobalTileIndex, TileTypes,),>,
+    tiles_to_delete:   Vec<GlobalTileIndex,>,
 }
 
 impl HotChunkSystem {
     pub fn new() -> Self {
         HotChunkSystem {
-            event_reader:     None,
-            chunks_to_load:   Vec::with_capacity(10,),
-            chunks_to_unload: Vec::with_capacity(10,),
+            event_reader:      None,
+            tile_event_reader: None,
+            chunks_to_load:    Vec::with_capacity(10,),
+            chunks_to_unload:  Vec::with_capacity(10,),
+            rechunk_to:        None,
+            tiles_to_change:   Vec::with_capacity(10,),
+            tiles_to_delete:   Vec::with_capacity(10,),
         }
     }
 }
@@ -55,11 +70,14 @@ impl<'a,> System<'a,> for HotChunkSystem {
         WriteStorage<'a, Transform,>,
         WriteStorage<'a, IsIngameEntity,>,
         WriteStorage<'a, Flipped,>,
+        ReadStorage<'a, PlayerBase,>,
         Option<Write<'a, GameSessionData,>,>,
         Option<Write<'a, EventChannel<ChunkEvent,>,>,>,
+        Option<Write<'a, EventChannel<TileEvent,>,>,>,
         Option<Read<'a, SavegamePaths,>,>,
         Option<Read<'a, GameSprites,>,>,
         Option<Read<'a, RenderConfig,>,>,
+        Option<Read<'a, WorldGenTables,>,>,
     );
 
     fn run(
@@ -71,26 +89,40 @@ impl<'a,> System<'a,> for HotChunkSystem {
             transforms,
             ingame_entities,
             flipped,
+            players,
             session_data,
             chunk_events,
+            tile_events,
             paths,
             game_sprites,
             render_config,
+            world_gen_tables,
         ): Self::SystemData,
     ) {
         if let (
             Some(mut session_data,),
-            Some(chunk_events,),
+            Some(mut chunk_events,),
+            Some(mut tile_events,),
             Some(paths,),
             Some(game_sprites,),
             Some(render_config,),
+            Some(world_gen_tables,),
         ) = (
             session_data,
             chunk_events,
+            tile_events,
             paths,
             game_sprites,
             render_config,
+            world_gen_tables,
         ) {
+            // New chunks get placed on the side of the planet's seam the player is on.
+            if let Some((transform, _,),) = (&transforms, &players,).join().next() {
+                session_data
+                    .planet
+                    .set_render_focus(transform.translation().x,);
+            }
+
             let mut tile_gen = TileGenerationStorages {
                 entities:           entities,
                 tile_base:          tag_tiles,
@@ -99,7 +131,8 @@ impl<'a,> System<'a,> for HotChunkSystem {
                 ingame_entity:      ingame_entities,
                 game_sprites:       game_sprites,
                 render_config:      render_config,
-                flipped_vertical:   flipped
+                flipped_vertical:   flipped,
+                world_gen_tables:   world_gen_tables,
             };
 
             if let Some(ref mut event_reader,) = self.event_reader {
@@ -111,49 +144,137 @@ impl<'a,> System<'a,> for HotChunkSystem {
                         ChunkEvent::RequestingUnload(chunk_index,) => {
                             self.chunks_to_unload.push(chunk_index,);
                         },
+                        ChunkEvent::RequestingRechunk(chunk_dim,) => {
+                            self.rechunk_to = Some(chunk_dim,);
+                        },
                         _ => continue,
                     };
                 }
 
-                for chunk_id in self.chunks_to_unload.drain(0 ..,) {
-                    session_data
+                if let Some(chunk_dim,) = self.rechunk_to.take() {
+                    // Pending requests use the old chunk dimension, the players request their chunks again afterwards.
+                    self.chunks_to_load.clear();
+                    self.chunks_to_unload.clear();
+                    self.tiles_to_change.clear();
+                    self.tiles_to_delete.clear();
+
+                    let loaded_chunks: Vec<ChunkIndex,> = session_data
                         .planet
-                        .save_chunk(chunk_id, paths.chunk_dir_path.clone(),);
+                        .iter_chunks()
+                        .map(|(&chunk_id, _,)| chunk_id,)
+                        .collect();
+                    // Changed chunks are re-chunked from their unsaved tiles.
+                    for chunk_id in loaded_chunks {
+                        session_data
+                            .planet
+                            .unload_chunk(chunk_id, &tile_gen.entities,);
+                    }
+
+                    match session_data.rechunk(&paths, chunk_dim, &tile_gen.world_gen_tables,) {
+                        Ok(_,) => chunk_events.single_write(ChunkEvent::Rechunked(chunk_dim,),),
+                        Err(_,) => chunk_events.single_write(ChunkEvent::FailedRechunk(chunk_dim,),),
+                    }
+                }
+
+                // Changed chunks are kept in memory and written by the next save of the session,
+                // so the stored chunks match the stored session and players, unless too many pile up.
+                for chunk_id in self.chunks_to_unload.drain(0 ..,) {
                     session_data
                         .planet
-                        .delete_chunk(chunk_id, &tile_gen.entities,);
+                        .unload_chunk(chunk_id, &tile_gen.entities,);
                 }
+                // Failures are logged, the chunks stay in memory until the next try.
+                let _ = session_data.planet.limit_unsaved_chunks(&paths,);
 
                 for chunk_id in self.chunks_to_load.drain(0 ..,) {
-                    let chunk_file_path = paths
-                        .chunk_dir_path
-                        .clone()
-                        .join(
-                            &{
-                                (chunk_id.1 * session_data.planet.planet_dim.0 + chunk_id.0) as u64
-                            }
-                            .to_string(),
-                        )
-                        .with_extension("ron",);
-
-                    if chunk_file_path.is_file() {
-                        session_data.planet.load_chunk(
-                            chunk_id,
-                            chunk_file_path.clone(),
-                            &mut tile_gen,
-                        );
+                    let is_loaded = |planet: &Planet| match planet.get_chunk(chunk_id,) {
+                        Ok(Some(_,),) => true,
+                        _ => false,
+                    };
+
+                    if is_loaded(&session_data.planet,) {
+                        // Requested again, e.g. by players entering the game after it was loaded.
+                        #[cfg(feature = "debug")]
+                        debug!("| {:?} is already loaded.", chunk_id);
                     }
-                    else if chunk_file_path.is_dir() {
-                        error!("| Chunk file path is a directory?! {:?}", chunk_file_path);
+                    else {
+                        match session_data
+                            .planet
+                            .is_chunk_stored(&paths.chunk_dir_path, chunk_id,)
+                        {
+                            Ok(true,) => {
+                                session_data.planet.load_chunk(
+                                    chunk_id,
+                                    &paths.chunk_dir_path,
+                                    &mut tile_gen,
+                                );
+                            },
+                            Ok(false,) => session_data.planet.new_chunk(chunk_id, &mut tile_gen,),
+                            // Stays unloaded and gets reported as failed, generating it would replace the stored one.
+                            Err(_,) => {},
+                        }
+                    }
+
+                    if is_loaded(&session_data.planet,) {
+                        chunk_events.single_write(ChunkEvent::Loaded(chunk_id,),);
                     }
                     else {
-                        session_data.planet.new_chunk(chunk_id, &mut tile_gen,);
+                        chunk_events.single_write(ChunkEvent::FailedLoad(chunk_id,),);
                     }
                 }
             }
             else {
                 error!("| No event ReaderId found for HotChunkSystem.");
             }
+
+            // Tiles get changed after loading chunks, so requests for freshly loaded chunks succeed.
+            if let Some(ref mut tile_event_reader,) = self.tile_event_reader {
+                for &event in tile_events.read(tile_event_reader,) {
+                    match event {
+                        TileEvent::RequestingChange(global_tile, tile_type,) => {
+                            self.tiles_to_change.push((global_tile, tile_type,),);
+                        },
+                        TileEvent::RequestDeletion(global_tile,) => {
+                            self.tiles_to_delete.push(global_tile,);
+                        },
+                        _ => continue,
+                    };
+                }
+
+                for (global_tile, tile_type,) in self.tiles_to_change.drain(0 ..,) {
+                    // Failures are reported as `TileEvent::FailedChange` by `set_tile`.
+                    let _ = session_data.planet.set_tile(
+                        global_tile,
+                        tile_type,
+                        &mut tile_gen,
+                        &mut tile_events,
+                    );
+                }
+
+                for global_tile in self.tiles_to_delete.drain(0 ..,) {
+                    let drillable = match session_data.planet.get_tile(global_tile,) {
+                        Ok(lookup,) => lookup.tile_type.is_drillable(),
+                        Err(_,) => false,
+                    };
+                    let deleted = drillable
+                        && session_data
+                            .planet
+                            .set_tile(global_tile, TileTypes::Empty, &mut tile_gen, &mut tile_events,)
+                            .is_ok();
+
+                    if deleted {
+                        tile_events.single_write(TileEvent::Deleted(global_tile,),);
+                    }
+                    else {
+                        #[cfg(feature = "debug")]
+                        debug!("| Could not delete {}.", global_tile);
+                        tile_events.single_write(TileEvent::FailedDelete(global_tile,),);
+                    }
+                }
+            }
+            else {
+                error!("| No tile event ReaderId found for HotChunkSystem.");
+            }
         }
         else {
             error!("| Resources not found.");
@@ -168,5 +289,9 @@ impl<'a,> System<'a,> for HotChunkSystem {
             res.fetch_mut::<EventChannel<ChunkEvent,>>()
                 .register_reader(),
         );
+        self.tile_event_reader = Some(
+            res.fetch_mut::<EventChannel<TileEvent,>>()
+                .register_reader(),
+        );
     }
 }
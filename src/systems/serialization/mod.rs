@@ -1,7 +1,11 @@
+mod autosave;
 mod de;
 mod ingame_chunk_hotloading;
 mod ser;
 
 pub use self::{
-    de::DeSavegameSystem, ingame_chunk_hotloading::HotChunkSystem, ser::SerSavegameSystem,
+    autosave::AutosaveSystem,
+    de::{DePlayersSystem, DeSavegameSystem},
+    ingame_chunk_hotloading::HotChunkSystem,
+    ser::SerSavegameSystem,
 };
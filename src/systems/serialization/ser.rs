@@ -11,46 +11,79 @@ use serde::Serializer;
 
 use amethyst::{
     core::{
-        specs::saveload::{
-            DeserializeComponents, SerializeComponents, U64Marker, U64MarkerAllocator,
+        specs::{
+            error::NoError,
+            saveload::{DeserializeComponents, SerializeComponents, U64Marker, U64MarkerAllocator},
         },
         timing::Time,
+        transform::components::Transform,
     },
     ecs::prelude::*,
 };
 
 use crate::{
-    components::for_characters::PlayerBase,
-    entities::tile::TileTypes,
+    components::{
+        for_characters::{player::Position, Engine, FuelTank, PartOf, PlayerBase},
+        physics::{Dynamics, PhysicalProperties},
+    },
+    entities::{player_parts::PlayerParts, tile::TileTypes},
     resources::{
         ingame::{
             game_world::{Chunk, ChunkIndex, Planet, TileIndex},
-            GameSessionData, SavegamePaths,
+            GameSessionData, SaveTransaction, SavegamePaths,
         },
         RenderConfig,
     },
 };
 
-/// TODO: Serialize players.
+/// The stored components of players and their ship parts, in the order they are written to `players.ron`.
+pub type PlayerStorages<'a,> = (
+    ReadStorage<'a, PlayerBase,>,
+    ReadStorage<'a, PlayerParts,>,
+    ReadStorage<'a, PartOf,>,
+    ReadStorage<'a, Transform,>,
+    ReadStorage<'a, Position,>,
+    ReadStorage<'a, Dynamics,>,
+    ReadStorage<'a, PhysicalProperties,>,
+    ReadStorage<'a, Engine,>,
+    ReadStorage<'a, FuelTank,>,
+);
+
 /// Creates a savegame by calling different serialization systems, based on the current [GameSessionData](struct.GameSessionData.html).
+/// Players and their ship parts are written to `players.ron`, every entity marked with a `U64Marker` gets stored.
 /// Uses `.ron` format.
 pub struct SerSavegameSystem;
 
 impl<'a,> System<'a,> for SerSavegameSystem {
     type SystemData = (
-        Option<Read<'a, GameSessionData,>,>,
+        Option<Write<'a, GameSessionData,>,>,
         Option<Read<'a, RenderConfig,>,>,
         Option<Read<'a, SavegamePaths,>,>,
+        Entities<'a,>,
+        ReadStorage<'a, U64Marker,>,
+        PlayerStorages<'a,>,
     );
 
-    fn run(&mut self, (session_data, render_config, paths,): Self::SystemData,) {
+    fn run(
+        &mut self,
+        (session_data, render_config, paths, entities, markers, player_storages,): Self::SystemData,
+    ) {
         #[cfg(feature = "debug")]
         debug!("+------------");
 
-        if let (Some(session_data,), Some(_render_config,), Some(paths,),) =
+        if let (Some(mut session_data,), Some(_render_config,), Some(paths,),) =
             (session_data, render_config, paths,)
         {
-            session_data.save(&paths,);
+            // Players, session data and chunks get committed together, or the previous save stays as it is.
+            let saved = SaveTransaction::new(&paths.savegame_dir_path,)
+                .map_err(|e| error!("| Could not start saving: {:?}", e),)
+                .and_then(|mut transaction| {
+                    stage_players(&paths, &entities, &markers, &player_storages, &mut transaction,)?;
+                    session_data.save_with(&paths, transaction,)
+                },);
+            if saved.is_err() {
+                error!("| Savegame has not been saved, the previous save is kept.");
+            }
         }
         else {
             error!("| Resources not found.")
@@ -59,3 +92,47 @@ impl<'a,> System<'a,> for SerSavegameSystem {
         debug!("+------------");
     }
 }
+
+/// Stages all marked players and ship parts for the players file of the savegame.
+fn stage_players(
+    paths: &SavegamePaths,
+    entities: &Entities<'_,>,
+    markers: &ReadStorage<'_, U64Marker,>,
+    player_storages: &PlayerStorages<'_,>,
+    transaction: &mut SaveTransaction,
+) -> Result<(), (),> {
+    let players_text = players_file_text(entities, markers, player_storages,)?;
+    if let Err(e,) = transaction.write(&paths.players_file_path, players_text.as_bytes(),) {
+        error!(
+            "| Writing players at '{:?}' threw error: {:?}",
+            paths.players_file_path.clone(),
+            e
+        );
+        return Err((),);
+    }
+
+    #[cfg(feature = "debug")]
+    debug!("| Staged players for {:?}.", paths.players_file_path);
+    Ok((),)
+}
+
+/// The content of `players.ron`: all marked players and ship parts.
+/// Has no format version of its own, changing it requires a new [`SAVEGAME_VERSION`](../../resources/ingame/constant.SAVEGAME_VERSION.html).
+pub fn players_file_text(
+    entities: &Entities<'_,>,
+    markers: &ReadStorage<'_, U64Marker,>,
+    player_storages: &PlayerStorages<'_,>,
+) -> Result<String, (),> {
+    let mut ser_players = ron::ser::Serializer::new(Some(Default::default(),), true,);
+    if let Err(e,) = SerializeComponents::<NoError, U64Marker,>::serialize(
+        player_storages,
+        entities,
+        markers,
+        &mut ser_players,
+    ) {
+        error!("| Serializing players threw error: {:?}", e);
+        return Err((),);
+    }
+
+    Ok(ser_players.into_output_string(),)
+}
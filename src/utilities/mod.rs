@@ -1,6 +1,16 @@
+mod overview_map;
+mod planet_report;
+mod ron_loading;
 mod spritesheet_loading;
+mod tool_args;
 
 pub use self::spritesheet_loading::{
     load_image_png, load_spritesheet, 
     load_image_png_tracked, load_spritesheet_tracked,
 };
+pub use self::{
+    overview_map::{MapPalette, OverviewMap},
+    planet_report::{DepthBand, MarketValues, PlanetReport},
+    ron_loading::{load_ron, RonLoadError},
+    tool_args::{parse_dim, parse_seed, DEFAULT_TABLES_DIR},
+};
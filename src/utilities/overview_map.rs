@@ -0,0 +1,214 @@
+use std::{fs, io::BufWriter, path::Path};
+
+use png::{self, HasParameters};
+
+use crate::{
+    entities::tile::TileTypes,
+    resources::{
+        ingame::{
+            game_world::{
+                generation, generation::WorldGenTables, ChunkIndex, GlobalTileIndex, Planet,
+            },
+            GameSessionData, SavegamePaths,
+        },
+        RenderConfig,
+    },
+};
+
+/// The colours of an [`OverviewMap`](struct.OverviewMap.html), as (red, green, blue).
+/// Loaded from `Prefabs/map_palette.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize,)]
+pub struct MapPalette {
+    /// Used for tile types missing in `tiles`.
+    pub unknown: (u8, u8, u8,),
+    /// Used for the chunk grid lines.
+    pub grid:    (u8, u8, u8,),
+    pub tiles:   Vec<(TileTypes, (u8, u8, u8,),),>,
+}
+
+impl Default for MapPalette {
+    fn default() -> Self {
+        MapPalette {
+            unknown: (255, 0, 255,),
+            grid:    (255, 255, 255,),
+            tiles:   Vec::new(),
+        }
+    }
+}
+
+impl MapPalette {
+    /// The colour of the given tile type.
+    pub fn colour(&self, tile_type: TileTypes,) -> (u8, u8, u8,) {
+        self.tiles
+            .iter()
+            .find(|&&(tile, _,)| tile == tile_type,)
+            .map(|&(_, colour,)| colour,)
+            .unwrap_or(self.unknown,)
+    }
+}
+
+/// The tile types of a whole planet, e.g. to export them as an image for balancing the world generation.
+/// Created without a running game, so it works without a window or GPU.
+#[derive(Debug, Clone,)]
+pub struct OverviewMap {
+    /// The world seed of the planet.
+    pub seed:       u64,
+    /// The size of the planet in tiles (rows, columns).
+    pub tile_count: (u64, u64,),
+    /// The chunk dimension of the planet, used for the grid lines.
+    pub chunk_dim:  (u64, u64,),
+    // All tiles, row by row.
+    tiles:          Vec<TileTypes,>,
+}
+
+impl OverviewMap {
+    /// Collects all tiles of the given planet.
+    /// Chunks stored in `chunk_dir_path` are read from disk, all other chunks get generated.
+    pub fn from_planet(
+        planet: &Planet,
+        chunk_dir_path: Option<&Path,>,
+        tables: &WorldGenTables,
+    ) -> Result<OverviewMap, (),> {
+        let tile_count = planet.tile_count();
+        let mut rv = OverviewMap {
+            seed: planet.seed,
+            tile_count,
+            chunk_dim: planet.chunk_dim,
+            tiles: vec![TileTypes::Empty; (tile_count.0 * tile_count.1) as usize],
+        };
+
+        for row in 0 .. planet.planet_dim.0 {
+            for col in 0 .. planet.planet_dim.1 {
+                let chunk_id = ChunkIndex(row, col,);
+                let stored_tiles = match chunk_dir_path {
+                    Some(chunk_dir_path,) => {
+                        match planet.read_chunk_tiles(chunk_dir_path, chunk_id,) {
+                            Ok(tiles,) => tiles,
+                            Err(e,) => {
+                                error!("| Error reading {:?}: {:?}.", chunk_id, e);
+                                return Err((),);
+                            },
+                        }
+                    },
+                    None => None,
+                };
+                let tiles = stored_tiles
+                    .unwrap_or_else(|| generation::chunk_tiles(planet, tables, chunk_id,),);
+
+                for (&tile_id, &tile_type,) in tiles.iter() {
+                    match GlobalTileIndex::from_indices(planet, chunk_id, tile_id,) {
+                        Ok(global_tile,) => {
+                            let index = global_tile.0 * tile_count.1 + global_tile.1;
+                            rv.tiles[index as usize] = tile_type;
+                        },
+                        Err(e,) => {
+                            warn!("| Skipping {:?} of {:?}: {:?}.", tile_id, chunk_id, e);
+                        },
+                    }
+                }
+            }
+        }
+
+        Ok(rv,)
+    }
+
+    /// Collects all tiles of a savegame, see [`from_planet`](struct.OverviewMap.html#method.from_planet).
+    /// The savegame is only read, see [`GameSessionData::read_savegame`](struct.GameSessionData.html#method.read_savegame).
+    pub fn from_savegame(
+        paths: &SavegamePaths,
+        tables: &WorldGenTables,
+    ) -> Result<OverviewMap, (),> {
+        let session_data = GameSessionData::read_savegame(paths,).map_err(|e| {
+            error!("| {}", e);
+        },)?;
+        Self::from_planet(&session_data.planet, Some(&paths.chunk_dir_path,), tables,)
+    }
+
+    /// Generates all tiles of a new planet with the given seed, see [`from_planet`](struct.OverviewMap.html#method.from_planet).
+    pub fn from_seed(
+        seed: u64,
+        planet_dim: (u64, u64,),
+        chunk_dim: (u64, u64,),
+        tables: &WorldGenTables,
+    ) -> Result<OverviewMap, (),> {
+        let planet = Planet::new(planet_dim, chunk_dim, seed, &RenderConfig::default(),);
+        Self::from_planet(&planet, None, tables,)
+    }
+
+    /// The tile type at the given global position (rows, columns).
+    pub fn get(&self, row: u64, col: u64,) -> Option<TileTypes,> {
+        if row < self.tile_count.0 && col < self.tile_count.1 {
+            self.tiles
+                .get((row * self.tile_count.1 + col) as usize,)
+                .cloned()
+        }
+        else {
+            None
+        }
+    }
+
+    /// Writes the map as an RGB PNG, each tile being `pixels_per_tile` × `pixels_per_tile` pixels.
+    /// Chunk borders get marked with one pixel wide lines, if `grid_lines` is set.
+    pub fn write_png(
+        &self,
+        path: &Path,
+        palette: &MapPalette,
+        pixels_per_tile: u32,
+        grid_lines: bool,
+    ) -> Result<(), (),> {
+        let scale = u64::from(pixels_per_tile.max(1,),);
+        let (height, width,) = (self.tile_count.0 * scale, self.tile_count.1 * scale,);
+        if height == 0
+            || width == 0
+            || height > u64::from(u32::max_value(),)
+            || width > u64::from(u32::max_value(),)
+        {
+            error!("| Cannot export a map of {}x{} pixels.", height, width);
+            return Err((),);
+        }
+
+        let chunk_pixels = (
+            self.chunk_dim.0.max(1,) * scale,
+            self.chunk_dim.1.max(1,) * scale,
+        );
+        let mut data = Vec::with_capacity((height * width * 3) as usize,);
+        for y in 0 .. height {
+            for x in 0 .. width {
+                let is_grid = grid_lines && (y % chunk_pixels.0 == 0 || x % chunk_pixels.1 == 0);
+                let colour = if is_grid {
+                    palette.grid
+                }
+                else {
+                    match self.get(y / scale, x / scale,) {
+                        Some(tile_type,) => palette.colour(tile_type,),
+                        None => palette.unknown,
+                    }
+                };
+                data.push(colour.0,);
+                data.push(colour.1,);
+                data.push(colour.2,);
+            }
+        }
+
+        let file = match fs::File::create(path,) {
+            Ok(rv,) => rv,
+            Err(e,) => {
+                error!("| Could not create {:?}: {:?}.", path, e);
+                return Err((),);
+            },
+        };
+
+        let mut encoder = png::Encoder::new(BufWriter::new(file,), width as u32, height as u32,);
+        encoder.set(png::ColorType::RGB,).set(png::BitDepth::Eight,);
+        let result = encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data,),);
+
+        if let Err(e,) = result {
+            error!("| Error writing {:?}: {:?}.", path, e);
+            return Err((),);
+        }
+
+        Ok((),)
+    }
+}
@@ -0,0 +1,323 @@
+use std::{collections::BTreeMap, io::Write};
+
+use crate::entities::tile::TileTypes;
+
+use super::OverviewMap;
+
+/// What a single tile sells for, used to estimate the value of a planet.
+/// Loaded from `Prefabs/market_values.ron`.
+#[derive(Debug, Clone, Serialize, Deserialize,)]
+pub struct MarketValues {
+    /// Tiles worth at least this much count as valuable ores.
+    pub valuable: f32,
+    /// Tile types not listed are worthless.
+    pub values:   Vec<(TileTypes, f32,),>,
+}
+
+impl Default for MarketValues {
+    fn default() -> Self {
+        MarketValues {
+            valuable: 1.0,
+            values:   Vec::new(),
+        }
+    }
+}
+
+impl MarketValues {
+    /// The value of a single tile of the given type.
+    pub fn value(&self, tile_type: TileTypes,) -> f32 {
+        self.values
+            .iter()
+            .find(|&&(tile, _,)| tile == tile_type,)
+            .map(|&(_, value,)| value,)
+            .unwrap_or(0.0,)
+    }
+
+    pub fn is_valuable(&self, tile_type: TileTypes,) -> bool {
+        self.value(tile_type,) >= self.valuable
+    }
+}
+
+/// The statistics of a range of rows of a planet.
+#[derive(Debug, Clone,)]
+pub struct DepthBand {
+    /// The first and last row of the band, both inclusive.
+    pub rows:             (u64, u64,),
+    pub tile_count:       u64,
+    pub histogram:        BTreeMap<TileTypes, u64,>,
+    /// The summed up value of all tiles in this band.
+    pub total_value:      f64,
+    pub valuable_count:   u64,
+    /// The average distance in tiles from a valuable ore in this band to the closest other valuable ore.
+    /// `None` if there are no two valuable ores on the planet.
+    pub avg_ore_distance: Option<f64,>,
+}
+
+impl DepthBand {
+    fn new(rows: (u64, u64,),) -> DepthBand {
+        DepthBand {
+            rows,
+            tile_count: 0,
+            histogram: BTreeMap::new(),
+            total_value: 0.0,
+            valuable_count: 0,
+            avg_ore_distance: None,
+        }
+    }
+
+    fn add(&mut self, tile_type: TileTypes, value: f64, is_valuable: bool,) {
+        self.tile_count += 1;
+        *self.histogram.entry(tile_type,).or_insert(0,) += 1;
+        self.total_value += value;
+        if is_valuable {
+            self.valuable_count += 1;
+        }
+    }
+
+    /// The expected value of a random tile in this band.
+    pub fn value_per_tile(&self,) -> f64 {
+        if self.tile_count == 0 {
+            0.0
+        }
+        else {
+            self.total_value / self.tile_count as f64
+        }
+    }
+
+    /// The share of the given tile type among all tiles of this band.
+    pub fn share(&self, tile_type: TileTypes,) -> f64 {
+        match self.histogram.get(&tile_type,) {
+            Some(&count,) if self.tile_count > 0 => count as f64 / self.tile_count as f64,
+            _ => 0.0,
+        }
+    }
+
+    // The histogram, most common tile types first.
+    fn sorted_histogram(&self,) -> Vec<(TileTypes, u64,),> {
+        let mut rv: Vec<(TileTypes, u64,),> = self
+            .histogram
+            .iter()
+            .map(|(&tile, &count,)| (tile, count,),)
+            .collect();
+        rv.sort_by(|a, b| b.1.cmp(&a.1,).then(a.0.cmp(&b.0,),),);
+        rv
+    }
+}
+
+/// Tile statistics of a whole planet, split into bands of equal depth, to compare ore tables and seeds.
+#[derive(Debug, Clone,)]
+pub struct PlanetReport {
+    pub seed:       u64,
+    /// The size of the planet in tiles (rows, columns).
+    pub tile_count: (u64, u64,),
+    pub bands:      Vec<DepthBand,>,
+    /// The statistics of the whole planet.
+    pub total:      DepthBand,
+}
+
+impl PlanetReport {
+    /// Scans all tiles of the given map.
+    /// The rows are split into `band_count` bands of (almost) equal height.
+    pub fn new(map: &OverviewMap, values: &MarketValues, band_count: u64,) -> PlanetReport {
+        let tile_count = map.tile_count;
+        let band_count = band_count.max(1,).min(tile_count.0.max(1,),);
+
+        let band_of_row = |row: u64| (row * band_count / tile_count.0.max(1,)) as usize;
+        let mut bands: Vec<DepthBand,> = (0 .. band_count)
+            .map(|band| {
+                let first_row = (band * tile_count.0 + band_count - 1) / band_count;
+                let last_row = ((band + 1) * tile_count.0 + band_count - 1) / band_count;
+                DepthBand::new((first_row, last_row.saturating_sub(1,),),)
+            },)
+            .collect();
+        let mut total = DepthBand::new((0, tile_count.0.saturating_sub(1,),),);
+        let mut ore_distances: Vec<(f64, u64,),> = vec![(0.0, 0); bands.len()];
+
+        for row in 0 .. tile_count.0 {
+            let band_index = band_of_row(row,);
+            for col in 0 .. tile_count.1 {
+                let tile_type = match map.get(row, col,) {
+                    Some(tile_type,) => tile_type,
+                    None => continue,
+                };
+                let value = f64::from(values.value(tile_type,),);
+
+                let is_valuable = values.is_valuable(tile_type,);
+                bands[band_index].add(tile_type, value, is_valuable,);
+                total.add(tile_type, value, is_valuable,);
+
+                if is_valuable {
+                    if let Some(distance,) = nearest_valuable(map, values, row, col,) {
+                        ore_distances[band_index].0 += distance;
+                        ore_distances[band_index].1 += 1;
+                    }
+                }
+            }
+        }
+
+        for (band, &(distance_sum, count,),) in bands.iter_mut().zip(ore_distances.iter(),) {
+            if count > 0 {
+                band.avg_ore_distance = Some(distance_sum / count as f64,);
+            }
+        }
+        let (distance_sum, count,) = ore_distances.iter().fold((0.0, 0,), |acc, &(sum, count,)| {
+            (acc.0 + sum, acc.1 + count,)
+        },);
+        if count > 0 {
+            total.avg_ore_distance = Some(distance_sum / count as f64,);
+        }
+
+        PlanetReport {
+            seed: map.seed,
+            tile_count,
+            bands,
+            total,
+        }
+    }
+
+    /// Writes the report in a human readable form.
+    pub fn write_text<W: Write,>(&self, writer: &mut W,) -> std::io::Result<(),> {
+        writeln!(
+            writer,
+            "Planet with seed {}, {}x{} tiles",
+            self.seed, self.tile_count.0, self.tile_count.1
+        )?;
+
+        let bands = self
+            .bands
+            .iter()
+            .map(|band| (false, band,),)
+            .chain(Some((true, &self.total,),),);
+
+        for (is_total, band,) in bands {
+            writeln!(writer)?;
+            if is_total {
+                writeln!(writer, "Whole planet")?;
+            }
+            else {
+                writeln!(
+                    writer,
+                    "Rows {} - {} (depth {:.2} - {:.2})",
+                    band.rows.0,
+                    band.rows.1,
+                    band.rows.0 as f64 / self.tile_count.0.max(1) as f64,
+                    (band.rows.1 + 1) as f64 / self.tile_count.0.max(1) as f64,
+                )?;
+            }
+            writeln!(
+                writer,
+                "  value per tile: {:.2}, total value: {:.0}",
+                band.value_per_tile(),
+                band.total_value
+            )?;
+            match band.avg_ore_distance {
+                Some(distance,) => {
+                    writeln!(
+                        writer,
+                        "  valuable ores: {}, average distance to the next one: {:.2} tiles",
+                        band.valuable_count, distance
+                    )?
+                },
+                None => writeln!(writer, "  valuable ores: {}", band.valuable_count)?,
+            }
+            for (tile_type, count,) in band.sorted_histogram() {
+                writeln!(
+                    writer,
+                    "  {:<16} {:>9} {:>7.2}%",
+                    format!("{:?}", tile_type),
+                    count,
+                    100.0 * band.share(tile_type)
+                )?;
+            }
+        }
+
+        Ok((),)
+    }
+
+    /// The header line matching [`write_csv`](struct.PlanetReport.html#method.write_csv).
+    pub fn write_csv_header<W: Write,>(writer: &mut W,) -> std::io::Result<(),> {
+        writeln!(
+            writer,
+            "seed,band,first_row,last_row,tile,count,share,value_per_tile,valuable_count,avg_ore_distance"
+        )
+    }
+
+    /// Writes one line per band and tile type, without a header,
+    /// so the reports of many planets can be written into a single file.
+    /// The whole planet is written as band `all`.
+    pub fn write_csv<W: Write,>(&self, writer: &mut W,) -> std::io::Result<(),> {
+        let bands = self
+            .bands
+            .iter()
+            .enumerate()
+            .map(|(index, band,)| (index.to_string(), band,),)
+            .chain(Some(("all".to_string(), &self.total,),),);
+
+        for (name, band,) in bands {
+            let distance = band
+                .avg_ore_distance
+                .map(|distance| format!("{:.3}", distance),)
+                .unwrap_or_default();
+            for (tile_type, count,) in band.sorted_histogram() {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{:?},{},{:.6},{:.3},{},{}",
+                    self.seed,
+                    name,
+                    band.rows.0,
+                    band.rows.1,
+                    tile_type,
+                    count,
+                    band.share(tile_type),
+                    band.value_per_tile(),
+                    band.valuable_count,
+                    distance
+                )?;
+            }
+        }
+
+        Ok((),)
+    }
+}
+
+/// The distance to the closest other valuable tile, searching in growing squares around the given tile.
+/// The planet wraps around in x-direction, so the distance is measured the shorter way around.
+fn nearest_valuable(map: &OverviewMap, values: &MarketValues, row: u64, col: u64,) -> Option<f64,> {
+    let (rows, cols,) = (map.tile_count.0 as i64, map.tile_count.1 as i64,);
+    let max_radius = rows.max(cols / 2 + 1,);
+    let mut best: Option<f64,> = None;
+
+    for radius in 1 ..= max_radius {
+        // Every tile outside of this square is farther away than the closest one found so far.
+        if let Some(distance,) = best {
+            if distance <= radius as f64 {
+                break;
+            }
+        }
+
+        for d_row in -radius ..= radius {
+            let other_row = row as i64 + d_row;
+            if other_row < 0 || other_row >= rows {
+                continue;
+            }
+            // Only the border of the square, the inside was checked with smaller radii.
+            let step = if d_row.abs() == radius { 1 } else { 2 * radius };
+            let mut d_col = -radius;
+            while d_col <= radius {
+                let other_col = (((col as i64 + d_col) % cols) + cols) % cols;
+                let is_valuable = map
+                    .get(other_row as u64, other_col as u64,)
+                    .map_or(false, |tile_type| values.is_valuable(tile_type,),);
+                if is_valuable && !(d_row == 0 && other_col as u64 == col) {
+                    let distance = ((d_row * d_row + d_col * d_col) as f64).sqrt();
+                    if best.map_or(true, |best| distance < best,) {
+                        best = Some(distance,);
+                    }
+                }
+                d_col += step;
+            }
+        }
+    }
+
+    best
+}
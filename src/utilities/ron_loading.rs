@@ -0,0 +1,41 @@
+use std::{fmt, fs, io, path::Path};
+
+use ron;
+use serde::de::DeserializeOwned;
+
+/// Problems loading a RON file, e.g. one of the world generation tables.
+#[derive(Debug,)]
+pub enum RonLoadError {
+    /// The file could not be opened.
+    Io(io::Error,),
+    /// The file's content does not match the expected structure.
+    Deserialize(ron::de::Error,),
+}
+
+impl fmt::Display for RonLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match *self {
+            RonLoadError::Io(ref e,) => write!(f, "could not open the file: {}", e),
+            RonLoadError::Deserialize(ref e,) => write!(f, "invalid content: {}", e),
+        }
+    }
+}
+
+impl From<io::Error,> for RonLoadError {
+    fn from(e: io::Error,) -> Self {
+        RonLoadError::Io(e,)
+    }
+}
+
+impl From<ron::de::Error,> for RonLoadError {
+    fn from(e: ron::de::Error,) -> Self {
+        RonLoadError::Deserialize(e,)
+    }
+}
+
+/// Deserializes the RON file at the given path, e.g. a table from `Prefabs`.
+/// Callers decide how to handle missing or broken files, usually by falling back to a default.
+pub fn load_ron<T: DeserializeOwned,>(path: &Path,) -> Result<T, RonLoadError,> {
+    let file = fs::File::open(path,)?;
+    Ok(ron::de::from_reader(&file,)?,)
+}
@@ -0,0 +1,50 @@
+//! Command line arguments shared by the tools in `src/bin`.
+//! All tools take a planet as `<planet_dim> <chunk_dim> <seed>`, and the world generation tables as `--tables <dir>`.
+
+/// The directory `--tables <dir>` defaults to.
+pub const DEFAULT_TABLES_DIR: &str = "./Prefabs/world_gen";
+
+/// Parses a dimension given as `<rows>x<columns>`, e.g. `8x16`.
+pub fn parse_dim(arg: &str,) -> Result<(u64, u64,), String,> {
+    let mut parts = arg.split('x',).map(|part| part.trim().parse::<u64>(),);
+    match (parts.next(), parts.next(), parts.next(),) {
+        (Some(Ok(rows,),), Some(Ok(cols,),), None,) if rows > 0 && cols > 0 => Ok((rows, cols,),),
+        _ => {
+            Err(format!(
+                "Invalid dimension {:?}, expected <rows>x<columns>.",
+                arg
+            ),)
+        },
+    }
+}
+
+/// Parses a world seed.
+pub fn parse_seed(arg: &str,) -> Result<u64, String,> {
+    arg.parse::<u64>()
+        .map_err(|e| format!("Invalid seed {:?}: {}.", arg, e),)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dimensions() {
+        assert_eq!(parse_dim("8x16"), Ok((8, 16,),));
+        assert_eq!(parse_dim(" 4 x 2 "), Ok((4, 2,),));
+    }
+
+    #[test]
+    fn rejects_invalid_dimensions() {
+        for arg in ["", "8", "8x", "x16", "0x16", "8x16x2", "8,16", "-8x16",].iter() {
+            assert!(parse_dim(arg).is_err(), "{:?} was accepted", arg);
+        }
+    }
+
+    #[test]
+    fn parses_seeds() {
+        assert_eq!(parse_seed("1234"), Ok(1234));
+        assert!(parse_seed("-1").is_err());
+        assert!(parse_seed("seed").is_err());
+    }
+}